@@ -20,12 +20,19 @@ cargo build --release
 echo "Hereafter, all outputs to stdout and stderr are redirected to the log file [ ${log_file} ]."
 exec >> "${log_file}" 2>&1
 
+#WebSub callback server
+if [[ "$(jq '.websub != null' './conf/config.json')" == 'true' ]]; then
+    ./target/release/rsst websub &
+fi
+
 while true; do
     echo "--------------- $(date) ---------------"
     ./target/release/rsst
     if [[ $? != 0 ]]; then
         sleep 60
-        ./target/release/rsst #retry
+        #`rsst` exits with non-zero when any feed failed (even if the others succeeded) or it crashed.
+        #Network errors are already retried by `rsst` itself, so this retry is for the failures which last a while (e.g. a crash or a site down for maintenance).
+        ./target/release/rsst
         if [[ $? != 0 ]]; then
             curl --silent \
                  -X POST \
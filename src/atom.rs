@@ -3,181 +3,670 @@ use std::hash::Hash;
 use std::hash::Hasher;
 use std::rc::Rc;
 
+use chrono::{DateTime, Utc};
+use quick_xml::events::BytesStart;
 use quick_xml::events::Event;
 use quick_xml::Reader;
+use regex::Regex;
 
+use super::date;
 use super::feedconfig::FeedConfig;
+use super::html;
+use super::media;
+use super::media::Enclosure;
+use super::namespace;
+use super::schedule;
+use super::schedule::UpdateHints;
+use super::urlutil;
+use super::xmlutil;
 
 #[derive(Debug)]
 pub struct Atom {
-    title: String,
+    title: TextConstruct,
     id: String,
+    link: Option<String>,
+    hub_url: Option<String>,
+    self_url: Option<String>,
+    update_hints: UpdateHints,
+    authors: Vec<String>,
     entry_list: Vec<AtomEntry>,
+    warning_list: Vec<String>,
     feed_config: Rc<FeedConfig>,
 }
 
 #[derive(Debug, Clone)]
 pub struct AtomEntry {
-    title: String,
+    title: TextConstruct,
     id: String,
+    link: Option<String>,
     updated: String,
-    summary: Option<String>,
-    content: Option<String>,
+    published: Option<String>,
+    parsed_date: Option<DateTime<Utc>>,
+    authors: Vec<String>,
+    categories: Vec<String>,
+    enclosures: Vec<Enclosure>,
+    thumbnail: Option<String>,
+    summary: Option<TextConstruct>,
+    content: Option<TextConstruct>,
     feed_config: Rc<FeedConfig>,
 }
 
+//`type` attribute of `<title>`, `<summary>` and `<content>`.
+#[derive(Debug, Clone, Copy)]
+enum TextType {
+    Text,
+    Html,
+    Xhtml,
+}
+
+//Represents the value of `<title>`, `<summary>` or `<content>`.
+//Triggers which render HTML should use `get_html()`, and the others should use `get_plain_text()`.
+#[derive(Debug, Clone)]
+pub struct TextConstruct {
+    value: String,
+    html: String,
+    plain_text: String,
+}
+
 #[derive(Debug)]
 enum TagType {
     Other,
     Feed,
     FeedTitle,
     FeedId,
+    FeedAuthor,
     Entry,
     EntryTitle,
     EntryId,
     EntryUpdated,
+    EntryPublished,
     EntrySummary,
     EntryContent,
+    EntryAuthor,
+    PersonName,
+    PersonEmail,
+    FeedUpdatePeriod,
+    FeedUpdateFrequency,
+    MediaGroup,
+    MediaContent,
+}
+
+//Elements without namespaces are also accepted as some feeds forget to declare the Atom namespace.
+fn is_atom_namespace(ns: Option<&[u8]>) -> bool {
+    matches!(ns, None | Some(namespace::ATOM) | Some(namespace::ATOM_0_3))
+}
+
+impl TextType {
+    fn new(e: &BytesStart) -> Self {
+        match xmlutil::get_attribute(e, b"type").as_deref() {
+            Some("html") | Some("text/html") => Self::Html,
+            Some("xhtml") | Some("application/xhtml+xml") => Self::Xhtml,
+            _ => Self::Text,
+        }
+    }
+}
+
+impl TextConstruct {
+    fn new(text_type: TextType, value: String) -> Self {
+        let (html, plain_text) = match text_type {
+            TextType::Text => (html::escape(&value), value.clone()),
+            TextType::Html | TextType::Xhtml => (value.clone(), html::to_plain_text(&value)),
+        };
+        TextConstruct {
+            value,
+            html,
+            plain_text,
+        }
+    }
+
+    //The value as written in the feed, that is, an unescaped text for `type="text"` and `type="html"`, and an inner markup of `<div>` for `type="xhtml"`.
+    pub fn get_value(&self) -> &str {
+        &self.value
+    }
+
+    pub fn get_html(&self) -> &str {
+        &self.html
+    }
+
+    pub fn get_plain_text(&self) -> &str {
+        &self.plain_text
+    }
+}
+
+//Extracts the inner markup of the `<div>` which wraps XHTML content.
+fn unwrap_xhtml_div(markup: &str) -> String {
+    match Regex::new(r"(?s)^\s*<([\w.-]+:)?div\b[^>]*>(.*)</([\w.-]+:)?div\s*>\s*$")
+        .unwrap()
+        .captures(markup)
+    {
+        Some(c) => c[2].trim().to_string(),
+        None => markup.trim().to_string(),
+    }
 }
 
 impl Atom {
     pub fn new(xml: &str, feed_config: &FeedConfig) -> Self {
         let mut ret = Atom {
-            title: String::new(),
+            title: TextConstruct::new(TextType::Text, String::new()),
             id: String::new(),
+            link: None,
+            hub_url: None,
+            self_url: None,
+            update_hints: UpdateHints::default(),
+            authors: Vec::new(),
             entry_list: Vec::new(),
+            warning_list: Vec::new(),
             feed_config: Rc::new(feed_config.clone()),
         };
 
+        let repaired_xml: String;
+        let xml: &str = if feed_config.is_lenient_mode {
+            let (s, warning_list) = xmlutil::repair(xml);
+            repaired_xml = s;
+            ret.warning_list = warning_list;
+            &repaired_xml
+        } else {
+            xml
+        };
+
         let mut reader = Reader::from_str(xml);
-        reader.trim_text(true);
         reader.expand_empty_elements(true);
+        reader.check_end_names(!feed_config.is_lenient_mode);
 
         let mut buf: Vec<u8> = Vec::new();
+        let mut ns_buf: Vec<u8> = Vec::new();
 
         let mut tag_stack: Vec<TagType> = Vec::new();
 
+        //Qualified names of open elements.
+        let mut name_stack: Vec<Vec<u8>> = Vec::new();
+
+        //`xml:base` in effect for each open element.
+        let mut base_stack: Vec<Option<String>> = Vec::new();
+
+        //Text of each open element.
+        //A text can be split into several fragments (e.g. by comments or CDATA sections), so we concatenate them
+        // and assign the result when the element is closed.
+        let mut text_stack: Vec<String> = Vec::new();
+
+        //Type and start position of the text construct being read.
+        //The position is used to extract the raw markup of XHTML content.
+        let mut text_construct_start: Option<(TextType, usize)> = None;
+
+        //`<name>` and `<email>` of the `<author>` being read.
+        let mut person: (Option<String>, Option<String>) = (None, None);
+
+        //`<sy:updatePeriod>` and `<sy:updateFrequency>` are interpreted together after parsing.
+        let mut update_period: Option<String> = None;
+        let mut update_frequency: Option<String> = None;
+
         loop {
             buf.clear();
 
-            match reader.read_event(&mut buf) {
-                Ok(Event::Eof) => {
+            let position: usize = reader.buffer_position();
+
+            match reader.read_namespaced_event(&mut buf, &mut ns_buf) {
+                Ok((_, Event::Eof)) => {
                     break;
                 }
 
-                Ok(Event::Start(ref e)) => match e.name() {
-                    b"feed" => {
-                        tag_stack.push(TagType::Feed);
-                    }
-                    b"entry" => {
-                        tag_stack.push(TagType::Entry);
-                        ret.entry_list.push(AtomEntry::new(&ret.feed_config));
-                    }
-                    b"title" => match tag_stack.last().unwrap() {
-                        TagType::Feed => {
-                            tag_stack.push(TagType::FeedTitle);
-                        }
-                        TagType::Entry => {
-                            tag_stack.push(TagType::EntryTitle);
-                        }
-                        _ => (),
-                    },
-                    b"id" => match tag_stack.last().unwrap() {
-                        TagType::Feed => {
-                            tag_stack.push(TagType::FeedId);
+                Ok((ns, Event::Start(ref e))) => {
+                    name_stack.push(e.name().to_vec());
+                    text_stack.push(String::new());
+                    //`xml:base` may itself be relative to that of the parent.
+                    let parent_base: Option<String> = base_stack.last().cloned().flatten();
+                    let base: Option<String> = match xmlutil::get_attribute(e, b"xml:base") {
+                        Some(b) => Some(urlutil::resolve(parent_base.as_deref(), &b)),
+                        None => parent_base,
+                    };
+                    base_stack.push(base.clone());
+                    let tag_stack_depth: usize = tag_stack.len();
+                    match (is_atom_namespace(ns), e.local_name()) {
+                        //`rel` defaults to `alternate`, which is the link to the HTML page.
+                        (true, b"link") => {
+                            let rel: Option<String> = xmlutil::get_attribute(e, b"rel");
+                            let href: Option<String> = xmlutil::get_attribute(e, b"href")
+                                .map(|href| urlutil::resolve(base.as_deref(), &href));
+                            match (tag_stack.last().unwrap(), rel.as_deref()) {
+                                (TagType::Feed, None | Some("alternate")) if ret.link.is_none() => {
+                                    ret.link = href;
+                                }
+                                (TagType::Feed, Some("hub")) if ret.hub_url.is_none() => {
+                                    ret.hub_url = href;
+                                }
+                                (TagType::Feed, Some("self")) if ret.self_url.is_none() => {
+                                    ret.self_url = href;
+                                }
+                                (TagType::Entry, None | Some("alternate")) => {
+                                    let entry = ret.entry_list.last_mut().unwrap();
+                                    if entry.link.is_none() {
+                                        entry.link = href;
+                                    }
+                                }
+                                (TagType::Entry, Some("enclosure")) => {
+                                    if let Some(enclosure) = Enclosure::from_atom_link(e) {
+                                        let mut enclosure = enclosure;
+                                        enclosure.set_url(urlutil::resolve(
+                                            base.as_deref(),
+                                            enclosure.get_url(),
+                                        ));
+                                        ret.entry_list
+                                            .last_mut()
+                                            .unwrap()
+                                            .enclosures
+                                            .push(enclosure);
+                                    }
+                                }
+                                _ => (),
+                            }
+                            tag_stack.push(TagType::Other);
                         }
-                        TagType::Entry => {
-                            tag_stack.push(TagType::EntryId);
+                        (true, b"feed") => {
+                            tag_stack.push(TagType::Feed);
                         }
-                        _ => (),
-                    },
-                    b"updated" => match tag_stack.last().unwrap() {
-                        TagType::Entry => {
-                            tag_stack.push(TagType::EntryUpdated);
+                        (true, b"entry") => {
+                            tag_stack.push(TagType::Entry);
+                            ret.entry_list.push(AtomEntry::new(&ret.feed_config));
                         }
-                        TagType::Feed => {
+                        (true, b"title") => match tag_stack.last().unwrap() {
+                            TagType::Feed => {
+                                tag_stack.push(TagType::FeedTitle);
+                            }
+                            TagType::Entry => {
+                                tag_stack.push(TagType::EntryTitle);
+                            }
+                            _ => tag_stack.push(TagType::Other),
+                        },
+                        (true, b"id") => match tag_stack.last().unwrap() {
+                            TagType::Feed => {
+                                tag_stack.push(TagType::FeedId);
+                            }
+                            TagType::Entry => {
+                                tag_stack.push(TagType::EntryId);
+                            }
+                            _ => tag_stack.push(TagType::Other),
+                        },
+                        (true, b"updated") => match tag_stack.last().unwrap() {
+                            TagType::Entry => {
+                                tag_stack.push(TagType::EntryUpdated);
+                            }
+                            TagType::Feed => {
+                                tag_stack.push(TagType::Other);
+                            }
+                            _ => tag_stack.push(TagType::Other),
+                        },
+                        (true, b"published") => match tag_stack.last().unwrap() {
+                            TagType::Entry => {
+                                tag_stack.push(TagType::EntryPublished);
+                            }
+                            _ => tag_stack.push(TagType::Other),
+                        },
+                        (true, b"summary") => match tag_stack.last().unwrap() {
+                            TagType::Entry => {
+                                tag_stack.push(TagType::EntrySummary);
+                            }
+                            _ => tag_stack.push(TagType::Other),
+                        },
+                        (true, b"author") => match tag_stack.last().unwrap() {
+                            TagType::Feed => {
+                                tag_stack.push(TagType::FeedAuthor);
+                            }
+                            TagType::Entry => {
+                                tag_stack.push(TagType::EntryAuthor);
+                            }
+                            _ => tag_stack.push(TagType::Other),
+                        },
+                        (true, b"name") => match tag_stack.last().unwrap() {
+                            TagType::FeedAuthor | TagType::EntryAuthor => {
+                                tag_stack.push(TagType::PersonName);
+                            }
+                            _ => tag_stack.push(TagType::Other),
+                        },
+                        (true, b"email") => match tag_stack.last().unwrap() {
+                            TagType::FeedAuthor | TagType::EntryAuthor => {
+                                tag_stack.push(TagType::PersonEmail);
+                            }
+                            _ => tag_stack.push(TagType::Other),
+                        },
+                        //`label` is a human-readable version of `term`.
+                        (true, b"category") => {
+                            if let TagType::Entry = tag_stack.last().unwrap() {
+                                if let Some(category) = xmlutil::get_attribute(e, b"label")
+                                    .or_else(|| xmlutil::get_attribute(e, b"term"))
+                                {
+                                    ret.entry_list.last_mut().unwrap().categories.push(category);
+                                }
+                            }
                             tag_stack.push(TagType::Other);
                         }
-                        _ => (),
-                    },
-                    b"summary" => {
-                        if let TagType::Entry = tag_stack.last().unwrap() {
-                            tag_stack.push(TagType::EntrySummary);
-                        }
+                        (true, b"content") => match tag_stack.last().unwrap() {
+                            TagType::Entry => {
+                                tag_stack.push(TagType::EntryContent);
+                            }
+                            _ => tag_stack.push(TagType::Other),
+                        },
+                        _ => match (ns, e.local_name()) {
+                            (Some(namespace::MEDIA), b"group") => match tag_stack.last().unwrap() {
+                                TagType::Entry => {
+                                    tag_stack.push(TagType::MediaGroup);
+                                }
+                                _ => tag_stack.push(TagType::Other),
+                            },
+                            (Some(namespace::MEDIA), b"content") => match tag_stack.last().unwrap()
+                            {
+                                TagType::Entry | TagType::MediaGroup => {
+                                    if let Some(enclosure) = Enclosure::from_media_content(e) {
+                                        let mut enclosure = enclosure;
+                                        enclosure.set_url(urlutil::resolve(
+                                            base.as_deref(),
+                                            enclosure.get_url(),
+                                        ));
+                                        ret.entry_list
+                                            .last_mut()
+                                            .unwrap()
+                                            .enclosures
+                                            .push(enclosure);
+                                    }
+                                    tag_stack.push(TagType::MediaContent);
+                                }
+                                _ => tag_stack.push(TagType::Other),
+                            },
+                            (Some(namespace::MEDIA), b"thumbnail") => {
+                                if let TagType::Entry
+                                | TagType::MediaGroup
+                                | TagType::MediaContent = tag_stack.last().unwrap()
+                                {
+                                    let entry = ret.entry_list.last_mut().unwrap();
+                                    if entry.thumbnail.is_none() {
+                                        entry.thumbnail = media::get_thumbnail_url(e)
+                                            .map(|url| urlutil::resolve(base.as_deref(), &url));
+                                    }
+                                }
+                                tag_stack.push(TagType::Other);
+                            }
+                            (Some(namespace::SYNDICATION), b"updatePeriod") => {
+                                match tag_stack.last().unwrap() {
+                                    TagType::Feed => {
+                                        tag_stack.push(TagType::FeedUpdatePeriod);
+                                    }
+                                    _ => tag_stack.push(TagType::Other),
+                                }
+                            }
+                            (Some(namespace::SYNDICATION), b"updateFrequency") => {
+                                match tag_stack.last().unwrap() {
+                                    TagType::Feed => {
+                                        tag_stack.push(TagType::FeedUpdateFrequency);
+                                    }
+                                    _ => tag_stack.push(TagType::Other),
+                                }
+                            }
+                            _ => {
+                                tag_stack.push(TagType::Other);
+                            }
+                        },
                     }
-                    b"content" => {
-                        if let TagType::Entry = tag_stack.last().unwrap() {
-                            tag_stack.push(TagType::EntryContent);
-                        }
+                    if let Some(
+                        TagType::FeedTitle
+                        | TagType::EntryTitle
+                        | TagType::EntrySummary
+                        | TagType::EntryContent,
+                    ) = tag_stack.get(tag_stack_depth)
+                    {
+                        text_construct_start = Some((TextType::new(e), reader.buffer_position()));
                     }
-                    _ => {
-                        tag_stack.push(TagType::Other);
+                }
+
+                Ok((_, Event::Text(ref e))) => {
+                    if let Some(text) = text_stack.last_mut() {
+                        text.push_str(&e.unescape_and_decode(&reader).unwrap());
                     }
-                },
+                }
 
-                Ok(Event::End(_)) => {
-                    tag_stack.pop();
+                Ok((_, Event::CData(ref e))) => {
+                    if let Some(text) = text_stack.last_mut() {
+                        text.push_str(&e.unescape_and_decode(&reader).unwrap());
+                    }
                 }
 
-                Ok(Event::Text(ref e)) => {
-                    let text: String = e.unescape_and_decode(&reader).unwrap();
-                    match tag_stack.last().unwrap() {
-                        TagType::FeedTitle => {
-                            ret.title = text;
-                        }
-                        TagType::FeedId => {
-                            ret.id = text;
-                        }
-                        TagType::EntryTitle => {
-                            ret.entry_list.last_mut().unwrap().title = text;
+                Ok((_, Event::End(ref e))) => {
+                    //In lenient mode, end tags may mismatch.
+                    //Elements left open (e.g. `<br>` in an unescaped HTML) are implicitly closed, and stray end tags are ignored.
+                    let depth: usize =
+                        match name_stack.iter().rposition(|n| n.as_slice() == e.name()) {
+                            Some(depth) => depth,
+                            None => continue,
+                        };
+                    while tag_stack.len() > depth {
+                        name_stack.pop();
+                        base_stack.pop();
+                        let tag = tag_stack.pop().unwrap();
+                        let text: String = text_stack.pop().unwrap().trim().to_string();
+
+                        //Texts of unknown elements (e.g. `<div>` of XHTML content) are a part of the parent's text.
+                        if let TagType::Other = tag {
+                            if let Some(parent_text) = text_stack.last_mut() {
+                                parent_text.push_str(&text);
+                            }
+                            continue;
                         }
-                        TagType::EntryId => {
-                            let text = if ret.feed_config.is_golang_blog_mode {
-                                text.replace("tag:blog.golang.org,2013:", "https://")
-                            } else {
-                                text
+
+                        if let TagType::FeedTitle
+                        | TagType::EntryTitle
+                        | TagType::EntrySummary
+                        | TagType::EntryContent = tag
+                        {
+                            let (text_type, start) = text_construct_start.take().unwrap();
+                            let value: String = match text_type {
+                                TextType::Xhtml => unwrap_xhtml_div(&xml[start..position]),
+                                _ => text,
                             };
-                            ret.entry_list.last_mut().unwrap().id = text;
+                            if value.is_empty() {
+                                continue;
+                            }
+                            let text_construct = TextConstruct::new(text_type, value);
+                            match tag {
+                                TagType::FeedTitle => {
+                                    ret.title = text_construct;
+                                }
+                                TagType::EntryTitle => {
+                                    ret.entry_list.last_mut().unwrap().title = text_construct;
+                                }
+                                TagType::EntrySummary => {
+                                    ret.entry_list.last_mut().unwrap().summary =
+                                        Some(text_construct);
+                                }
+                                TagType::EntryContent => {
+                                    ret.entry_list.last_mut().unwrap().content =
+                                        Some(text_construct);
+                                }
+                                _ => (),
+                            }
+                            continue;
                         }
-                        TagType::EntryUpdated => {
-                            ret.entry_list.last_mut().unwrap().updated = text;
+
+                        if let TagType::FeedAuthor | TagType::EntryAuthor = tag {
+                            let (name, email) = std::mem::take(&mut person);
+                            if let Some(author) = name.or(email) {
+                                match tag {
+                                    TagType::FeedAuthor => ret.authors.push(author),
+                                    _ => ret.entry_list.last_mut().unwrap().authors.push(author),
+                                }
+                            }
+                            continue;
                         }
-                        TagType::EntrySummary => {
-                            ret.entry_list.last_mut().unwrap().summary = Some(text);
+
+                        if text.is_empty() {
+                            continue;
                         }
-                        TagType::EntryContent => {
-                            ret.entry_list.last_mut().unwrap().content = Some(text);
+
+                        match tag {
+                            TagType::PersonName => {
+                                person.0 = Some(text);
+                            }
+                            TagType::PersonEmail => {
+                                person.1 = Some(text);
+                            }
+                            TagType::FeedId => {
+                                ret.id = text;
+                            }
+                            TagType::FeedUpdatePeriod => {
+                                update_period = Some(text);
+                            }
+                            TagType::FeedUpdateFrequency => {
+                                update_frequency = Some(text);
+                            }
+                            TagType::EntryId => {
+                                let text = if ret.feed_config.is_golang_blog_mode {
+                                    text.replace("tag:blog.golang.org,2013:", "https://")
+                                } else {
+                                    text
+                                };
+                                ret.entry_list.last_mut().unwrap().id = text;
+                            }
+                            TagType::EntryUpdated => {
+                                ret.entry_list.last_mut().unwrap().updated = text;
+                            }
+                            TagType::EntryPublished => {
+                                ret.entry_list.last_mut().unwrap().published = Some(text);
+                            }
+                            _ => (),
                         }
-                        _ => (),
                     }
                 }
 
                 Err(e) => {
-                    panic!("Error at position {}: {:?}", reader.buffer_position(), e)
+                    let message =
+                        format!("Error at position {}: {:?}", reader.buffer_position(), e);
+                    if !feed_config.is_lenient_mode {
+                        panic!("{}", message);
+                    }
+                    ret.warning_list.push(message);
+                    break;
                 }
 
                 _ => (),
             }
         }
 
+        ret.update_hints.update_period =
+            schedule::parse_update_period(update_period.as_deref(), update_frequency.as_deref());
+
+        //A truncated entry is dropped.
+        if tag_stack.iter().any(|e| matches!(e, TagType::Entry)) {
+            ret.warning_list.push(format!(
+                "Dropped an unclosed entry: {:?}",
+                ret.entry_list.pop().map(|e| e.get_title().to_string())
+            ));
+        }
+
+        for entry in &mut ret.entry_list {
+            //An entry without `<author>` inherits that of the feed.
+            if entry.authors.is_empty() {
+                entry.authors = ret.authors.clone();
+            }
+
+            if entry.thumbnail.is_none() {
+                entry.thumbnail = entry
+                    .enclosures
+                    .iter()
+                    .find(|e| e.is_image())
+                    .map(|e| e.get_url().to_string());
+            }
+
+            //`<published>` is preferred as `<updated>` changes every time an entry is edited.
+            entry.parsed_date = entry
+                .published
+                .as_deref()
+                .and_then(date::parse)
+                .or_else(|| date::parse(&entry.updated));
+        }
+
         ret
     }
 
     pub fn hash_code(&self) -> String {
         let mut hasher = DefaultHasher::new();
-        self.title.hash(&mut hasher);
+        self.title.get_value().hash(&mut hasher);
         self.id.hash(&mut hasher);
         hasher.finish().to_string()
     }
 
     pub fn get_title(&self) -> &str {
-        &self.title
+        self.title.get_plain_text()
     }
 
     pub fn get_id(&self) -> &str {
         &self.id
     }
 
+    pub fn get_link(&self) -> &Option<String> {
+        &self.link
+    }
+
+    //`<link rel="hub">` (WebSub)
+    pub fn get_hub_url(&self) -> &Option<String> {
+        &self.hub_url
+    }
+
+    //`<link rel="self">`
+    pub fn get_self_url(&self) -> &Option<String> {
+        &self.self_url
+    }
+
+    //`<sy:updatePeriod>`
+    pub fn get_update_hints(&self) -> &UpdateHints {
+        &self.update_hints
+    }
+
+    pub fn get_authors(&self) -> &Vec<String> {
+        &self.authors
+    }
+
+    //Resolves relative URLs of the entries which were not resolved by `xml:base`.
+    //They are resolved against the feed link, or `document_url` (i.e. the URL the feed was fetched from) when the feed link is absent or also relative.
+    pub fn resolve_relative_urls(&mut self, document_url: &str) {
+        let base: String = match &self.link {
+            Some(link) => urlutil::resolve(Some(document_url), link),
+            None => document_url.to_string(),
+        };
+        let base: &str = if urlutil::is_absolute(&base) {
+            &base
+        } else {
+            document_url
+        };
+        for entry in &mut self.entry_list {
+            entry.link = entry
+                .link
+                .as_deref()
+                .map(|url| urlutil::resolve(Some(base), url));
+            entry.thumbnail = entry
+                .thumbnail
+                .as_deref()
+                .map(|url| urlutil::resolve(Some(base), url));
+            for enclosure in &mut entry.enclosures {
+                enclosure.set_url(urlutil::resolve(Some(base), enclosure.get_url()));
+            }
+        }
+    }
+
+    //Canonicalizes the links of the entries according to `url_canonicalization` of the feed config.
+    //This should be called after `resolve_relative_urls()` as relative URLs cannot be canonicalized.
+    pub fn canonicalize_urls(&mut self) {
+        if let Some(canonicalization) = &self.feed_config.url_canonicalization {
+            for entry in &mut self.entry_list {
+                entry.link = entry
+                    .link
+                    .as_deref()
+                    .map(|url| urlutil::canonicalize(url, canonicalization));
+            }
+        }
+    }
+
+    //problems found (and recovered from) in lenient mode
+    pub fn get_warning_list(&self) -> &Vec<String> {
+        &self.warning_list
+    }
+
     pub fn get_entry_list(&self) -> &Vec<AtomEntry> {
         &self.entry_list
     }
@@ -187,8 +676,15 @@ impl AtomEntry {
     fn new(feed_config: &Rc<FeedConfig>) -> Self {
         AtomEntry {
             id: String::new(),
-            title: String::new(),
+            link: None,
+            title: TextConstruct::new(TextType::Text, String::new()),
             updated: String::new(),
+            published: None,
+            parsed_date: None,
+            authors: Vec::new(),
+            categories: Vec::new(),
+            enclosures: Vec::new(),
+            thumbnail: None,
             summary: None,
             content: None,
             feed_config: Rc::clone(feed_config),
@@ -198,7 +694,7 @@ impl AtomEntry {
     //We intentionally omit `self.summary` and `self.content` as some feed suppliers often (e.g. everyday) update their values.
     pub fn hash_code(&self) -> String {
         let mut hasher = DefaultHasher::new();
-        self.title.hash(&mut hasher);
+        self.title.get_value().hash(&mut hasher);
         self.id.hash(&mut hasher);
         if !self.feed_config.should_omit_date_field_from_hash {
             self.updated.hash(&mut hasher);
@@ -207,21 +703,55 @@ impl AtomEntry {
     }
 
     pub fn get_title(&self) -> &str {
-        &self.title
+        self.title.get_plain_text()
     }
 
     pub fn get_id(&self) -> &str {
         &self.id
     }
 
+    //`<link rel="alternate">`, or `<id>` if absent (as `<id>` is often the permalink).
+    pub fn get_link(&self) -> &str {
+        self.link.as_deref().unwrap_or(&self.id)
+    }
+
     pub fn get_updated(&self) -> &str {
         &self.updated
     }
 
-    pub fn get_summary_or_content(&self) -> &Option<String> {
-        match self.summary {
-            Some(_) => &self.summary,
-            None => &self.content,
-        }
+    pub fn get_published(&self) -> &Option<String> {
+        &self.published
+    }
+
+    pub fn get_parsed_date(&self) -> &Option<DateTime<Utc>> {
+        &self.parsed_date
+    }
+
+    pub fn get_authors(&self) -> &Vec<String> {
+        &self.authors
+    }
+
+    pub fn get_categories(&self) -> &Vec<String> {
+        &self.categories
+    }
+
+    pub fn get_enclosures(&self) -> &Vec<Enclosure> {
+        &self.enclosures
+    }
+
+    pub fn get_thumbnail(&self) -> &Option<String> {
+        &self.thumbnail
+    }
+
+    pub fn get_feed_config(&self) -> &FeedConfig {
+        &self.feed_config
+    }
+
+    pub fn get_content(&self) -> Option<&TextConstruct> {
+        self.content.as_ref()
+    }
+
+    pub fn get_summary_or_content(&self) -> Option<&TextConstruct> {
+        self.summary.as_ref().or(self.content.as_ref())
     }
 }
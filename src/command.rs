@@ -1,40 +1,42 @@
-use std::io::{BufReader, BufWriter, Read, Write};
-use std::process::{Command, Stdio};
+use std::error::Error;
+use std::io::Write;
+use std::process::{Command, Output, Stdio};
+use std::thread;
 
 #[derive(Debug)]
 pub struct CommandOutput {
-    pub stdout: String,
+    //raw bytes as the output is not necessarily UTF-8 (e.g. a feed in Shift_JIS)
+    pub stdout: Vec<u8>,
     pub stderr: String,
     pub exit_status: i32,
 }
 
-pub fn run(command: &str, args: &Vec<&str>, input: &str) -> CommandOutput {
+//Fails when the command cannot be run (e.g. it is not found) or is killed by a signal.
+pub fn run(command: &str, args: &Vec<&str>, input: &str) -> Result<CommandOutput, Box<dyn Error>> {
     let mut child = Command::new(command)
         .args(args)
         .stdin(Stdio::piped())
         .stdout(Stdio::piped())
         .stderr(Stdio::piped())
         .spawn()
-        .unwrap();
-
-    let mut stdin = BufWriter::new(child.stdin.take().unwrap());
-    let mut stdout = BufReader::new(child.stdout.take().unwrap());
-    let mut stderr = BufReader::new(child.stderr.take().unwrap());
-
-    stdin.write_all(input.as_bytes()).unwrap();
-    drop(stdin);
-
-    let mut stdout_buf = String::new();
-    stdout.read_to_string(&mut stdout_buf).unwrap();
-
-    let mut stderr_buf = String::new();
-    stderr.read_to_string(&mut stderr_buf).unwrap();
-
-    let exit_status = child.wait().unwrap();
-
-    CommandOutput {
-        stdout: stdout_buf,
-        stderr: stderr_buf,
-        exit_status: exit_status.code().unwrap(),
-    }
+        .map_err(|e| format!("Failed to run `{}`: {}", command, e))?;
+
+    //Written in another thread so that the command is not blocked on a full stdout or stderr pipe while we write.
+    let mut stdin = child.stdin.take().unwrap();
+    let input: String = input.to_string();
+    let writer = thread::spawn(move || stdin.write_all(input.as_bytes()));
+
+    //reads stdout and stderr concurrently
+    let output: Output = child.wait_with_output()?;
+    //The command may exit without reading all the input, which is not an error.
+    let _ = writer.join();
+
+    Ok(CommandOutput {
+        stdout: output.stdout,
+        stderr: String::from_utf8_lossy(&output.stderr).to_string(),
+        exit_status: output
+            .status
+            .code()
+            .ok_or_else(|| format!("`{}` was killed by a signal.", command))?,
+    })
 }
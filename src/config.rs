@@ -1,44 +1,57 @@
-use std::fs::File;
-use std::io::BufRead;
-use std::io::BufReader;
+use std::error::Error;
+use std::fs;
+use std::time::Duration;
 
 use json;
 use json::JsonValue;
 use regex::Regex;
 
+use super::discord;
 use super::discord::DiscordNotification;
 use super::feedconfig::FeedConfig;
+use super::feedconfig::IdentityStrategy;
+use super::health;
+use super::http::Authentication;
+use super::http::ClientOptions;
+use super::http::HttpOptions;
+use super::scrape::ScrapeConfig;
+use super::template::Template;
 use super::trigger::Trigger;
+use super::twitter;
 use super::twitter::TwitterNotification;
+use super::urlutil::UrlCanonicalization;
+use super::webhook;
+use super::webhook::WebhookBody;
+use super::webhook::WebhookNotification;
+use super::websub::WebSubConfig;
 
 pub struct Config {
+    config_file: String,
     should_log_debug: bool,
     database_file: String,
     trigger_list: Vec<Box<dyn Trigger>>,
+    alert_trigger_list: Vec<Box<dyn Trigger>>,
     feed_config_list: Vec<FeedConfig>,
+    //top-level `http_options`, used for requests not tied to a feed (e.g. `rsst discover`)
+    http_options: HttpOptions,
+    websub_config: Option<WebSubConfig>,
 }
 
 impl Config {
     pub fn new(config_file: &str) -> Self {
         let mut ret = Config {
+            config_file: config_file.to_string(),
             should_log_debug: false,
             database_file: String::new(),
             trigger_list: Vec::new(),
+            alert_trigger_list: Vec::new(),
             feed_config_list: Vec::new(),
+            http_options: HttpOptions::new(),
+            websub_config: None,
         };
 
-        let json_string: String = {
-            let file: File = File::open(config_file).unwrap();
-
-            let comment_regex = Regex::new(r#"^\s*#.*"#).unwrap();
-
-            BufReader::new(file)
-                .lines()
-                .filter(|l| !comment_regex.is_match(l.as_ref().unwrap()))
-                .map(|l| l.unwrap())
-                .collect::<Vec<String>>()
-                .join("\n")
-        };
+        let json_string: String =
+            Config::remove_comments(&fs::read_to_string(config_file).unwrap());
 
         match json::parse(&json_string).unwrap() {
             JsonValue::Object(o) => {
@@ -51,6 +64,54 @@ impl Config {
                     .unwrap()
                     .to_string();
 
+                if let Some(JsonValue::Object(o)) = o.get("websub") {
+                    ret.websub_config = Some(WebSubConfig {
+                        callback_url: o.get("callback_url").unwrap().as_str().unwrap().to_string(),
+                        listen_address: o
+                            .get("listen_address")
+                            .map(|s| s.as_str().unwrap().to_string())
+                            .unwrap_or_else(|| "0.0.0.0:8080".to_string()),
+                        lease_duration: o
+                            .get("lease_duration")
+                            .and_then(Config::parse_duration)
+                            .unwrap_or(Duration::from_secs(10 * 24 * 60 * 60)),
+                    });
+                }
+
+                let max_entry_age: Option<Duration> =
+                    o.get("max_entry_age").and_then(Config::parse_duration);
+                let flood_threshold: Option<usize> =
+                    o.get("flood_threshold").and_then(Config::parse_threshold);
+                let should_send_flood_summary: bool = o
+                    .get("should_send_flood_summary")
+                    .map(|b| b.as_bool().unwrap())
+                    .unwrap_or(false);
+                let is_lenient_mode: bool = o
+                    .get("is_lenient_mode")
+                    .map(|b| b.as_bool().unwrap())
+                    .unwrap_or(false);
+                let url_canonicalization: Option<UrlCanonicalization> = o
+                    .get("url_canonicalization")
+                    .and_then(Config::parse_url_canonicalization);
+                let should_update_url_on_permanent_redirect: bool = o
+                    .get("should_update_url_on_permanent_redirect")
+                    .map(|b| b.as_bool().unwrap())
+                    .unwrap_or(false);
+                let should_honor_update_hints: bool = o
+                    .get("should_honor_update_hints")
+                    .map(|b| b.as_bool().unwrap())
+                    .unwrap_or(false);
+                let unhealthy_threshold: Option<usize> = match o.get("unhealthy_threshold") {
+                    Some(v) => Config::parse_threshold(v),
+                    None => Some(health::DEFAULT_UNHEALTHY_THRESHOLD),
+                };
+                let pause_threshold: Option<usize> =
+                    o.get("pause_threshold").and_then(Config::parse_threshold);
+                let http_options: HttpOptions = match o.get("http_options") {
+                    Some(v) => Config::parse_http_options(v, &HttpOptions::new()),
+                    None => HttpOptions::new(),
+                };
+
                 match o.get("feed_config_list").unwrap() {
                     JsonValue::Array(v) => {
                         ret.feed_config_list = v
@@ -66,6 +127,77 @@ impl Config {
                                     if let Some(b) = o.get("is_golang_blog_mode") {
                                         feed_config.is_golang_blog_mode = b.as_bool().unwrap();
                                     }
+                                    if let Some(b) = o.get("should_use_websub") {
+                                        feed_config.should_use_websub = b.as_bool().unwrap();
+                                    }
+                                    if let Some(JsonValue::Array(v)) = o.get("exec") {
+                                        feed_config.exec_command = Some(
+                                            v.iter()
+                                                .map(|s| s.as_str().unwrap().to_string())
+                                                .collect(),
+                                        );
+                                    }
+                                    if let Some(JsonValue::Object(o)) = o.get("scrape") {
+                                        feed_config.scrape_config =
+                                            Some(Config::parse_scrape_config(o));
+                                    }
+                                    if let Some(JsonValue::Object(o)) = o.get("templates") {
+                                        feed_config.template_map = o
+                                            .iter()
+                                            .map(|(k, v)| {
+                                                (k.to_string(), Template::new(v.as_str().unwrap()))
+                                            })
+                                            .collect();
+                                    }
+                                    if let Some(s) = o.get("identity_strategy") {
+                                        feed_config.identity_strategy =
+                                            IdentityStrategy::new(s.as_str().unwrap());
+                                    }
+                                    feed_config.max_entry_age = match o.get("max_entry_age") {
+                                        Some(v) => Config::parse_duration(v),
+                                        None => max_entry_age,
+                                    };
+                                    feed_config.flood_threshold = match o.get("flood_threshold") {
+                                        Some(v) => Config::parse_threshold(v),
+                                        None => flood_threshold,
+                                    };
+                                    feed_config.should_send_flood_summary =
+                                        match o.get("should_send_flood_summary") {
+                                            Some(b) => b.as_bool().unwrap(),
+                                            None => should_send_flood_summary,
+                                        };
+                                    feed_config.is_lenient_mode = match o.get("is_lenient_mode") {
+                                        Some(b) => b.as_bool().unwrap(),
+                                        None => is_lenient_mode,
+                                    };
+                                    feed_config.url_canonicalization =
+                                        match o.get("url_canonicalization") {
+                                            Some(v) => Config::parse_url_canonicalization(v),
+                                            None => url_canonicalization.clone(),
+                                        };
+                                    feed_config.http_options = match o.get("http_options") {
+                                        Some(v) => Config::parse_http_options(v, &http_options),
+                                        None => http_options.clone(),
+                                    };
+                                    feed_config.should_update_url_on_permanent_redirect =
+                                        match o.get("should_update_url_on_permanent_redirect") {
+                                            Some(b) => b.as_bool().unwrap(),
+                                            None => should_update_url_on_permanent_redirect,
+                                        };
+                                    feed_config.should_honor_update_hints =
+                                        match o.get("should_honor_update_hints") {
+                                            Some(b) => b.as_bool().unwrap(),
+                                            None => should_honor_update_hints,
+                                        };
+                                    feed_config.unhealthy_threshold =
+                                        match o.get("unhealthy_threshold") {
+                                            Some(v) => Config::parse_threshold(v),
+                                            None => unhealthy_threshold,
+                                        };
+                                    feed_config.pause_threshold = match o.get("pause_threshold") {
+                                        Some(v) => Config::parse_threshold(v),
+                                        None => pause_threshold,
+                                    };
                                     feed_config
                                 }
                                 _ => panic!(),
@@ -76,27 +208,44 @@ impl Config {
                 };
 
                 match o.get("triggers").unwrap() {
-                    JsonValue::Object(o) => {
-                        for field in o.iter() {
-                            match field {
-                                ("discord", JsonValue::Object(o)) => {
+                    JsonValue::Object(triggers) => {
+                        for (name, v) in triggers.iter() {
+                            match v {
+                                JsonValue::Object(o) => {
                                     if o.get("enabled").unwrap().as_bool().unwrap() {
-                                        ret.trigger_list.push(Box::new(DiscordNotification::new(
-                                            o.get("webhook_url").unwrap().as_str().unwrap(),
-                                        )));
+                                        ret.trigger_list.push(Config::build_trigger(
+                                            name,
+                                            o,
+                                            &http_options,
+                                        ));
                                     }
                                 }
-                                ("twitter", JsonValue::Object(o)) => {
-                                    if o.get("enabled").unwrap().as_bool().unwrap() {
-                                        ret.trigger_list.push(Box::new(TwitterNotification::new()));
+                                _ => panic!(),
+                            }
+                        }
+
+                        //Alert triggers are built regardless of `enabled`
+                        // so that e.g. a Discord channel can be dedicated to alerts.
+                        if let Some(JsonValue::Array(v)) = o.get("alert_triggers") {
+                            for name in v {
+                                let name: &str = name.as_str().unwrap();
+                                match triggers.get(name) {
+                                    Some(JsonValue::Object(o)) => {
+                                        ret.alert_trigger_list.push(Config::build_trigger(
+                                            name,
+                                            o,
+                                            &http_options,
+                                        ));
                                     }
+                                    _ => panic!("Unknown alert trigger: {}", name),
                                 }
-                                _ => panic!(),
                             }
                         }
                     }
                     _ => panic!(),
                 };
+
+                ret.http_options = http_options;
             }
             _ => panic!(),
         }
@@ -108,6 +257,286 @@ impl Config {
         ret
     }
 
+    //`http_options` of the trigger is merged with the top-level one as in feeds.
+    //`type` defaults to the name, and is needed to have several triggers of the same type (e.g. two webhooks).
+    fn build_trigger(
+        name: &str,
+        o: &json::object::Object,
+        http_options: &HttpOptions,
+    ) -> Box<dyn Trigger> {
+        let http_options: HttpOptions = match o.get("http_options") {
+            Some(v) => Config::parse_http_options(v, http_options),
+            None => http_options.clone(),
+        };
+        let trigger_type: &str = o.get("type").and_then(|s| s.as_str()).unwrap_or(name);
+        let get_template = |default: &str| {
+            Template::new(
+                o.get("template")
+                    .and_then(|s| s.as_str())
+                    .unwrap_or(default),
+            )
+        };
+        match trigger_type {
+            "discord" => Box::new(DiscordNotification::new(
+                name,
+                get_template(discord::DEFAULT_TEMPLATE),
+                o.get("webhook_url").unwrap().as_str().unwrap(),
+                &http_options,
+            )),
+            "twitter" => Box::new(TwitterNotification::new(
+                name,
+                get_template(twitter::DEFAULT_TEMPLATE),
+            )),
+            "webhook" => Box::new(WebhookNotification::new(
+                name,
+                get_template(webhook::DEFAULT_TEMPLATE),
+                o.get("method").and_then(|s| s.as_str()).unwrap_or("POST"),
+                o.get("url").unwrap().as_str().unwrap(),
+                match o.get("headers") {
+                    Some(JsonValue::Object(headers)) => headers
+                        .iter()
+                        .map(|(k, v)| (k.to_string(), v.as_str().unwrap().to_string()))
+                        .collect(),
+                    _ => Vec::new(),
+                },
+                WebhookBody::new(
+                    o.get("body_type")
+                        .and_then(|s| s.as_str())
+                        .unwrap_or("json"),
+                    o.get("body").unwrap_or(&JsonValue::Null),
+                ),
+                match o.get("success_status_codes") {
+                    Some(JsonValue::Array(v)) => v.iter().map(|e| e.as_u16().unwrap()).collect(),
+                    _ => Vec::new(),
+                },
+                &http_options,
+            )),
+            _ => panic!("Unknown trigger: {} (type `{}`)", name, trigger_type),
+        }
+    }
+
+    //Parses a human-readable duration such as `"30days"` or `"12h 30m"`.
+    //`null` is regarded as "not specified", which is useful to override a global setting per feed.
+    fn parse_duration(v: &JsonValue) -> Option<Duration> {
+        if v.is_null() {
+            return None;
+        }
+        let s: &str = v
+            .as_str()
+            .unwrap_or_else(|| panic!("Invalid duration: {}", v));
+        Some(
+            humantime::parse_duration(s)
+                .unwrap_or_else(|e| panic!("Invalid duration `{}`: {}", s, e)),
+        )
+    }
+
+    //A non-negative integer, or `null` which disables the feature (e.g. to override a global setting per feed).
+    fn parse_threshold(v: &JsonValue) -> Option<usize> {
+        if v.is_null() {
+            return None;
+        }
+        Some(
+            v.as_usize()
+                .unwrap_or_else(|| panic!("Invalid threshold: {}", v.dump())),
+        )
+    }
+
+    //A string, or `null` which disables the feature (e.g. to override a global setting per feed).
+    fn parse_optional_string(v: &JsonValue, name: &str) -> Option<String> {
+        if v.is_null() {
+            return None;
+        }
+        Some(
+            v.as_str()
+                .unwrap_or_else(|| panic!("Invalid `{}`: {}", name, v.dump()))
+                .to_string(),
+        )
+    }
+
+    //`true` enables the canonicalization with the default settings, and `false` or `null` disables it.
+    //An object enables it with the specified settings.
+    fn parse_url_canonicalization(v: &JsonValue) -> Option<UrlCanonicalization> {
+        match v {
+            JsonValue::Boolean(true) => Some(UrlCanonicalization::new()),
+            JsonValue::Object(o) => {
+                let mut ret = UrlCanonicalization::new();
+                if let Some(JsonValue::Array(v)) = o.get("stripped_query_parameter_list") {
+                    ret.stripped_query_parameter_list =
+                        v.iter().map(|s| s.as_str().unwrap().to_string()).collect();
+                }
+                if let Some(b) = o.get("should_remove_trailing_slash") {
+                    ret.should_remove_trailing_slash = b.as_bool().unwrap();
+                }
+                Some(ret)
+            }
+            _ => None,
+        }
+    }
+
+    fn parse_scrape_config(o: &json::object::Object) -> ScrapeConfig {
+        let get_selector = |key: &str| {
+            o.get(key)
+                .map(|s| ScrapeConfig::parse_selector(s.as_str().unwrap()))
+        };
+        ScrapeConfig {
+            item_selector: get_selector("item_selector").expect("`item_selector` is required."),
+            title_selector: get_selector("title_selector"),
+            link_selector: get_selector("link_selector"),
+            date_selector: get_selector("date_selector"),
+            summary_selector: get_selector("summary_selector"),
+        }
+    }
+
+    //Fields not specified in `v` are inherited from `base`, and headers are merged.
+    //`null` for `timeout` or `max_response_size` means unlimited.
+    fn parse_http_options(v: &JsonValue, base: &HttpOptions) -> HttpOptions {
+        let mut ret: HttpOptions = base.clone();
+        let o = match v {
+            JsonValue::Object(o) => o,
+            _ => panic!("`http_options` should be an object."),
+        };
+        if let Some(JsonValue::Object(headers)) = o.get("headers") {
+            for (name, value) in headers.iter() {
+                ret.set_header(name, value.as_str().unwrap());
+            }
+        }
+        if let Some(v) = o.get("authentication") {
+            ret.authentication = match v {
+                JsonValue::Null => None,
+                JsonValue::Object(o) => match o.get("type").unwrap().as_str().unwrap() {
+                    "basic" => Some(Authentication::Basic {
+                        username: o.get("username").unwrap().as_str().unwrap().to_string(),
+                        password: o
+                            .get("password")
+                            .and_then(|s| s.as_str())
+                            .map(|s| s.to_string()),
+                    }),
+                    "bearer" => Some(Authentication::Bearer {
+                        token: o.get("token").unwrap().as_str().unwrap().to_string(),
+                    }),
+                    t => panic!("Unknown authentication type: {}", t),
+                },
+                _ => panic!(),
+            };
+        }
+        if let Some(s) = o.get("user_agent") {
+            ret.user_agent = s.as_str().unwrap().to_string();
+        }
+        if let Some(v) = o.get("timeout") {
+            //`reqwest` has no way to disable the timeout per request, so a very long one is used instead.
+            ret.timeout = Config::parse_duration(v).unwrap_or(Duration::from_secs(u32::MAX as u64));
+        }
+        if let Some(v) = o.get("max_response_size") {
+            ret.max_response_size = if v.is_null() {
+                None
+            } else {
+                Some(
+                    v.as_u64()
+                        .unwrap_or_else(|| panic!("Invalid `max_response_size`: {}", v)),
+                )
+            };
+        }
+        //For the retries and the politeness, `null` disables them and invalid values are rejected.
+        if let Some(v) = o.get("max_retries") {
+            ret.max_retries = if v.is_null() {
+                0
+            } else {
+                v.as_u32()
+                    .unwrap_or_else(|| panic!("Invalid `max_retries`: {}", v))
+            };
+        }
+        if let Some(v) = o.get("retry_initial_delay") {
+            ret.retry_initial_delay = Config::parse_duration(v).unwrap_or(Duration::ZERO);
+        }
+        if let Some(v) = o.get("retry_max_delay") {
+            ret.retry_max_delay = Config::parse_duration(v).unwrap_or(Duration::ZERO);
+        }
+        if let Some(v) = o.get("politeness_delay") {
+            ret.politeness_delay = Config::parse_duration(v).unwrap_or(Duration::ZERO);
+        }
+        let client_options: &mut ClientOptions = &mut ret.client_options;
+        if let Some(v) = o.get("proxy") {
+            client_options.proxy = Config::parse_optional_string(v, "proxy");
+        }
+        if let Some(v) = o.get("ca_bundle_file") {
+            client_options.ca_bundle_file = Config::parse_optional_string(v, "ca_bundle_file");
+        }
+        if let Some(v) = o.get("client_certificate_file") {
+            client_options.client_certificate_file =
+                Config::parse_optional_string(v, "client_certificate_file");
+        }
+        if let Some(v) = o.get("client_key_file") {
+            client_options.client_key_file = Config::parse_optional_string(v, "client_key_file");
+        }
+        if let Some(b) = o.get("should_accept_invalid_certificates") {
+            client_options.should_accept_invalid_certificates = b.as_bool().unwrap();
+        }
+        ret
+    }
+
+    //Lines starting with `#` are comments.
+    fn remove_comments(contents: &str) -> String {
+        let comment_regex = Regex::new(r#"^\s*#.*"#).unwrap();
+        contents
+            .lines()
+            .filter(|l| !comment_regex.is_match(l))
+            .collect::<Vec<&str>>()
+            .join("\n")
+    }
+
+    //Rewrites `"url": "<old_url>"` of the feed in `feed_config_list` of the config file.
+    //The file is edited textually so that comments and formatting are preserved.
+    //The same URL may appear elsewhere (e.g. `url` of a webhook trigger), so each occurrence is tried
+    // and the one which changes exactly the `url` of the feed is adopted.
+    pub fn replace_feed_url(&self, old_url: &str, new_url: &str) -> Result<(), Box<dyn Error>> {
+        let contents: String = fs::read_to_string(&self.config_file)?;
+
+        let mut expected: JsonValue = json::parse(&Config::remove_comments(&contents))?;
+        let mut is_found = false;
+        for feed in expected["feed_config_list"].members_mut() {
+            if feed["url"] == old_url {
+                feed["url"] = new_url.into();
+                is_found = true;
+            }
+        }
+        if !is_found {
+            return Err(format!("`{}` is not found in {}", old_url, self.config_file).into());
+        }
+
+        let regex = Regex::new(&format!(
+            r#""url"(\s*):(\s*){}"#,
+            regex::escape(&json::stringify(old_url))
+        ))?;
+        let candidate_list: Vec<String> = regex
+            .captures_iter(&contents)
+            .map(|c| {
+                let m = c.get(0).unwrap();
+                format!(
+                    r#"{}"url"{}:{}{}{}"#,
+                    &contents[..m.start()],
+                    &c[1],
+                    &c[2],
+                    json::stringify(new_url),
+                    &contents[m.end()..]
+                )
+            })
+            .filter(|s| json::parse(&Config::remove_comments(s)).is_ok_and(|v| v == expected))
+            .collect();
+        match candidate_list.as_slice() {
+            [contents] => {
+                fs::write(&self.config_file, contents)?;
+                Ok(())
+            }
+            _ => Err(format!(
+                "`url` of the feed `{}` could not be located in {} ({} candidates)",
+                old_url,
+                self.config_file,
+                candidate_list.len()
+            )
+            .into()),
+        }
+    }
+
     pub fn get_should_log_debug(&self) -> &bool {
         &self.should_log_debug
     }
@@ -120,7 +549,19 @@ impl Config {
         &self.trigger_list
     }
 
+    pub fn get_alert_trigger_list(&self) -> &Vec<Box<dyn Trigger>> {
+        &self.alert_trigger_list
+    }
+
     pub fn get_feed_config_list(&self) -> &Vec<FeedConfig> {
         &self.feed_config_list
     }
+
+    pub fn get_http_options(&self) -> &HttpOptions {
+        &self.http_options
+    }
+
+    pub fn get_websub_config(&self) -> &Option<WebSubConfig> {
+        &self.websub_config
+    }
 }
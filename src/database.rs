@@ -1,11 +1,23 @@
 use std::rc::Rc;
+use std::time::Duration;
 
+use chrono::{DateTime, Utc};
+
+use json::JsonValue;
 use rusqlite::{params, types::Value, Connection};
 
 use super::atom::Atom;
 use super::atom::AtomEntry;
+use super::health::FeedHealth;
+use super::health::FetchAttempt;
+use super::http::Redirect;
+use super::media::Enclosure;
 use super::rss::Rss;
 use super::rss::RssItem;
+use super::schedule;
+use super::schedule::Schedule;
+use super::websub::Subscription;
+use super::websub::SubscriptionState;
 
 pub struct Database {
     db_connection: Connection,
@@ -16,6 +28,8 @@ impl Database {
     pub fn new(database_file: &str, should_drop_tables_first: bool) -> Self {
         let db_connection = Connection::open(database_file).unwrap();
         rusqlite::vtab::array::load_module(&db_connection).unwrap();
+        //The WebSub callback server may write to the database at the same time.
+        db_connection.busy_timeout(Duration::from_secs(10)).unwrap();
 
         //for debug
         if should_drop_tables_first {
@@ -25,6 +39,27 @@ impl Database {
             db_connection
                 .execute(r#"DROP TABLE IF EXISTS "feeds";"#, [])
                 .unwrap();
+            db_connection
+                .execute(r#"DROP TABLE IF EXISTS "redirects";"#, [])
+                .unwrap();
+            db_connection
+                .execute(r#"DROP TABLE IF EXISTS "websub_subscriptions";"#, [])
+                .unwrap();
+            db_connection
+                .execute(r#"DROP TABLE IF EXISTS "schedules";"#, [])
+                .unwrap();
+            db_connection
+                .execute(r#"DROP TABLE IF EXISTS "fetch_attempts";"#, [])
+                .unwrap();
+            db_connection
+                .execute(r#"DROP TABLE IF EXISTS "feed_health";"#, [])
+                .unwrap();
+            db_connection
+                .execute(r#"DROP TABLE IF EXISTS "feed_ids";"#, [])
+                .unwrap();
+            db_connection
+                .execute(r#"DROP TABLE IF EXISTS "discoveries";"#, [])
+                .unwrap();
         }
 
         Database::initialize_database(&db_connection);
@@ -60,12 +95,198 @@ impl Database {
                     "link"           TEXT,
                     "description"    TEXT,
                     "pub_date"       TEXT,
+                    "parsed_date"    DATETIME,
+                    "published"      TEXT,
+                    "authors"        TEXT,
+                    "categories"     TEXT,
+                    "content"        TEXT,
+                    "comments"       TEXT,
+                    "enclosures"     TEXT,
+                    "thumbnail"      TEXT,
                     FOREIGN KEY("parent_hash") REFERENCES feeds("hash")
                 )
                 "#,
                 [],
             )
             .unwrap();
+
+        //Represents the redirects observed in the latest fetch of each feed URL (i.e. `url` in the config).
+        //`permanent_url` is the URL reached only via permanent redirects (see `http::get_permanent_url()`).
+        db_connection
+            .execute(
+                r#"
+                CREATE TABLE IF NOT EXISTS "redirects" (
+                    "update_date"    DATETIME DEFAULT CURRENT_TIMESTAMP,
+                    "url"            TEXT PRIMARY KEY,
+                    "final_url"      TEXT NOT NULL,
+                    "permanent_url"  TEXT,
+                    "chain"          TEXT NOT NULL
+                )
+                "#,
+                [],
+            )
+            .unwrap();
+
+        //Represents each WebSub subscription.
+        db_connection
+            .execute(
+                r#"
+                CREATE TABLE IF NOT EXISTS "websub_subscriptions" (
+                    "update_date"       DATETIME NOT NULL,
+                    "topic_url"         TEXT PRIMARY KEY,
+                    "hub_url"           TEXT NOT NULL,
+                    "feed_url"          TEXT NOT NULL,
+                    "secret"            TEXT NOT NULL,
+                    "state"             TEXT NOT NULL,
+                    "lease_expiration"  DATETIME
+                )
+                "#,
+                [],
+            )
+            .unwrap();
+
+        //Represents when each feed URL (i.e. `url` in the config) should be fetched next, as hinted by the feed or the server.
+        //`skip_hours` and `skip_days` are JSON arrays (e.g. `[0, 1]` and `["Sat", "Sun"]`).
+        //Feeds without hints have no rows.
+        db_connection
+            .execute(
+                r#"
+                CREATE TABLE IF NOT EXISTS "schedules" (
+                    "update_date"      DATETIME DEFAULT CURRENT_TIMESTAMP,
+                    "url"              TEXT PRIMARY KEY,
+                    "next_fetch_date"  DATETIME NOT NULL,
+                    "skip_hours"       TEXT,
+                    "skip_days"        TEXT
+                )
+                "#,
+                [],
+            )
+            .unwrap();
+        //for databases created by older versions
+        for column in ["skip_hours", "skip_days"] {
+            Database::add_column_if_not_exists(db_connection, "schedules", column, "TEXT");
+        }
+
+        //Represents the identifier of each feed URL (i.e. `url` in the config), which scopes the hashes of its items.
+        //It is kept when `url` is updated to the URL the feed moved to, so that the items do not look new.
+        db_connection
+            .execute(
+                r#"
+                CREATE TABLE IF NOT EXISTS "feed_ids" (
+                    "update_date"  DATETIME DEFAULT CURRENT_TIMESTAMP,
+                    "url"          TEXT PRIMARY KEY,
+                    "id"           TEXT NOT NULL
+                )
+                "#,
+                [],
+            )
+            .unwrap();
+
+        //Represents the feed discovered in the HTML page of each feed URL (i.e. `url` in the config),
+        // so that the page need not be fetched on every run.
+        db_connection
+            .execute(
+                r#"
+                CREATE TABLE IF NOT EXISTS "discoveries" (
+                    "update_date"  DATETIME NOT NULL,
+                    "url"          TEXT PRIMARY KEY,
+                    "feed_url"     TEXT NOT NULL
+                )
+                "#,
+                [],
+            )
+            .unwrap();
+
+        //Represents each fetch of each feed URL (i.e. `url` in the config), whether it succeeded or not.
+        db_connection
+            .execute(
+                r#"
+                CREATE TABLE IF NOT EXISTS "fetch_attempts" (
+                    "id"           INTEGER PRIMARY KEY,
+                    "date"         DATETIME NOT NULL,
+                    "url"          TEXT NOT NULL,
+                    "status"       INTEGER,
+                    "duration_ms"  INTEGER NOT NULL,
+                    "error"        TEXT,
+                    "size"         INTEGER,
+                    "item_count"   INTEGER
+                )
+                "#,
+                [],
+            )
+            .unwrap();
+        db_connection
+            .execute(
+                r#"CREATE INDEX IF NOT EXISTS "fetch_attempts_url_date" ON "fetch_attempts" ("url", "date")"#,
+                [],
+            )
+            .unwrap();
+
+        //Represents the health of each feed URL, summarizing `fetch_attempts`.
+        db_connection
+            .execute(
+                r#"
+                CREATE TABLE IF NOT EXISTS "feed_health" (
+                    "url"                        TEXT PRIMARY KEY,
+                    "consecutive_failure_count"  INTEGER NOT NULL,
+                    "last_success_date"          DATETIME,
+                    "last_attempt_date"          DATETIME NOT NULL,
+                    "last_error"                 TEXT,
+                    "is_unhealthy"               INTEGER NOT NULL,
+                    "paused_date"                DATETIME
+                )
+                "#,
+                [],
+            )
+            .unwrap();
+
+        //for databases created by older versions
+        for (column, column_type) in [
+            ("parsed_date", "DATETIME"),
+            ("published", "TEXT"),
+            ("authors", "TEXT"),
+            ("categories", "TEXT"),
+            ("content", "TEXT"),
+            ("comments", "TEXT"),
+            ("enclosures", "TEXT"),
+            ("thumbnail", "TEXT"),
+        ] {
+            Database::add_column_if_not_exists(db_connection, "feed_items", column, column_type);
+        }
+    }
+
+    fn add_column_if_not_exists(
+        db_connection: &Connection,
+        table: &str,
+        column: &str,
+        column_type: &str,
+    ) {
+        let does_column_exist = db_connection
+            .prepare(&format!(r#"PRAGMA table_info("{}")"#, table))
+            .unwrap()
+            .query_map([], |r| r.get::<_, String>(1))
+            .unwrap()
+            .any(|e| e.unwrap() == column);
+        if !does_column_exist {
+            db_connection
+                .execute(
+                    &format!(
+                        r#"ALTER TABLE "{}" ADD COLUMN "{}" {}"#,
+                        table, column, column_type
+                    ),
+                    [],
+                )
+                .unwrap();
+        }
+    }
+
+    //Lists are stored as JSON arrays.
+    fn to_json_array(l: &[String]) -> String {
+        JsonValue::from(l.to_vec()).dump()
+    }
+
+    fn enclosures_to_json_array(l: &[Enclosure]) -> String {
+        JsonValue::from(l.iter().map(|e| e.to_json()).collect::<Vec<JsonValue>>()).dump()
     }
 
     pub fn does_feed_exist(&self, hash_code: &str) -> bool {
@@ -120,8 +341,11 @@ impl Database {
                 .execute(
                     r#"
                         INSERT INTO "feed_items"
-                        ("hash", "parent_hash", "title", "link", "description", "pub_date")
-                        VALUES (?, ?, ?, ?, ?, ?)
+                        (
+                            "hash", "parent_hash", "title", "link", "description", "pub_date", "parsed_date",
+                            "published", "authors", "categories", "content", "comments", "enclosures", "thumbnail"
+                        )
+                        VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
                     "#,
                     params![
                         rss_item.hash_code(),
@@ -129,7 +353,15 @@ impl Database {
                         rss_item.get_title(),
                         rss_item.get_link(),
                         rss_item.get_description(),
-                        rss_item.get_pub_date()
+                        rss_item.get_pub_date(),
+                        rss_item.get_parsed_pub_date(),
+                        rss_item.get_pub_date(),
+                        Database::to_json_array(rss_item.get_authors()),
+                        Database::to_json_array(rss_item.get_categories()),
+                        rss_item.get_content(),
+                        rss_item.get_comments(),
+                        Database::enclosures_to_json_array(rss_item.get_enclosures()),
+                        rss_item.get_thumbnail()
                     ],
                 )
                 .unwrap();
@@ -149,19 +381,383 @@ impl Database {
                 .execute(
                     r#"
                         INSERT INTO "feed_items"
-                        ("hash", "parent_hash", "title", "link", "description", "pub_date")
-                        VALUES (?, ?, ?, ?, ?, ?)
+                        (
+                            "hash", "parent_hash", "title", "link", "description", "pub_date", "parsed_date",
+                            "published", "authors", "categories", "content", "comments", "enclosures", "thumbnail"
+                        )
+                        VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
                     "#,
                     params![
                         atom_entry.hash_code(),
                         parent_hash,
                         atom_entry.get_title(),
-                        atom_entry.get_id(),
-                        atom_entry.get_summary_or_content(),
-                        atom_entry.get_updated()
+                        atom_entry.get_link(),
+                        atom_entry.get_summary_or_content().map(|e| e.get_value()),
+                        atom_entry.get_updated(),
+                        atom_entry.get_parsed_date(),
+                        atom_entry.get_published(),
+                        Database::to_json_array(atom_entry.get_authors()),
+                        Database::to_json_array(atom_entry.get_categories()),
+                        atom_entry.get_content().map(|e| e.get_value()),
+                        Option::<String>::None,
+                        Database::enclosures_to_json_array(atom_entry.get_enclosures()),
+                        atom_entry.get_thumbnail()
                     ],
                 )
                 .unwrap();
         }
     }
 }
+
+//redirects
+impl Database {
+    pub fn upsert_redirects(
+        &self,
+        url: &str,
+        final_url: &str,
+        permanent_url: &Option<String>,
+        redirect_list: &[Redirect],
+    ) {
+        self.db_connection
+            .execute(
+                r#"
+                    INSERT OR REPLACE INTO "redirects"
+                    ("update_date", "url", "final_url", "permanent_url", "chain")
+                    VALUES (CURRENT_TIMESTAMP, ?, ?, ?, ?)
+                "#,
+                params![
+                    url,
+                    final_url,
+                    permanent_url,
+                    JsonValue::from(
+                        redirect_list
+                            .iter()
+                            .map(|e| e.to_json())
+                            .collect::<Vec<JsonValue>>()
+                    )
+                    .dump()
+                ],
+            )
+            .unwrap();
+    }
+
+    pub fn delete_redirects(&self, url: &str) {
+        self.db_connection
+            .execute(r#"DELETE FROM "redirects" WHERE "url" = ?"#, [url])
+            .unwrap();
+    }
+
+    //Returns the feed URLs which have moved permanently to `permanent_url`.
+    pub fn select_urls_moved_to(&self, permanent_url: &str) -> Vec<String> {
+        self.db_connection
+            .prepare(r#"SELECT "url" FROM "redirects" WHERE "permanent_url" = ?"#)
+            .unwrap()
+            .query_map([permanent_url], |r| r.get(0))
+            .unwrap()
+            .map(|e| e.unwrap())
+            .collect()
+    }
+
+    //Returns `(url, permanent_url, update_date)` of the feed URLs which have moved permanently.
+    pub fn select_permanent_redirects(&self) -> Vec<(String, String, String)> {
+        self.db_connection
+            .prepare(
+                r#"
+                    SELECT "url", "permanent_url", "update_date" FROM "redirects"
+                    WHERE "permanent_url" IS NOT NULL
+                    ORDER BY "url"
+                "#,
+            )
+            .unwrap()
+            .query_map([], |r| Ok((r.get(0)?, r.get(1)?, r.get(2)?)))
+            .unwrap()
+            .map(|e| e.unwrap())
+            .collect()
+    }
+}
+
+//websub
+impl Database {
+    pub fn upsert_websub_subscription(&self, subscription: &Subscription) {
+        self.db_connection
+            .execute(
+                r#"
+                    INSERT OR REPLACE INTO "websub_subscriptions"
+                    ("update_date", "topic_url", "hub_url", "feed_url", "secret", "state", "lease_expiration")
+                    VALUES (?, ?, ?, ?, ?, ?, ?)
+                "#,
+                params![
+                    subscription.update_date,
+                    subscription.topic_url,
+                    subscription.hub_url,
+                    subscription.feed_url,
+                    subscription.secret,
+                    subscription.state.as_str(),
+                    subscription.lease_expiration
+                ],
+            )
+            .unwrap();
+    }
+
+    pub fn update_websub_subscription_state(
+        &self,
+        topic_url: &str,
+        state: SubscriptionState,
+        lease_expiration: Option<DateTime<Utc>>,
+    ) {
+        self.db_connection
+            .execute(
+                r#"
+                    UPDATE "websub_subscriptions"
+                    SET "update_date" = ?, "state" = ?, "lease_expiration" = ?
+                    WHERE "topic_url" = ?
+                "#,
+                params![Utc::now(), state.as_str(), lease_expiration, topic_url],
+            )
+            .unwrap();
+    }
+
+    pub fn select_websub_subscription(&self, topic_url: &str) -> Option<Subscription> {
+        self.db_connection
+            .prepare(
+                r#"
+                    SELECT "update_date", "topic_url", "hub_url", "feed_url", "secret", "state", "lease_expiration"
+                    FROM "websub_subscriptions" WHERE "topic_url" = ?
+                "#,
+            )
+            .unwrap()
+            .query_map([topic_url], |r| {
+                Ok(Subscription {
+                    update_date: r.get(0)?,
+                    topic_url: r.get(1)?,
+                    hub_url: r.get(2)?,
+                    feed_url: r.get(3)?,
+                    secret: r.get(4)?,
+                    state: SubscriptionState::new(&r.get::<_, String>(5)?),
+                    lease_expiration: r.get(6)?,
+                })
+            })
+            .unwrap()
+            .map(|e| e.unwrap())
+            .next()
+    }
+}
+
+//schedules
+impl Database {
+    //`next_fetch_date` is always set when there are skipped hours or days.
+    pub fn upsert_schedule(&self, url: &str, schedule: &Schedule) {
+        match schedule.next_fetch_date {
+            Some(next_fetch_date) => self
+                .db_connection
+                .execute(
+                    r#"
+                        INSERT OR REPLACE INTO "schedules"
+                        ("update_date", "url", "next_fetch_date", "skip_hours", "skip_days")
+                        VALUES (CURRENT_TIMESTAMP, ?, ?, ?, ?)
+                    "#,
+                    params![
+                        url,
+                        next_fetch_date,
+                        JsonValue::from(schedule.skip_hour_list.clone()).dump(),
+                        Database::to_json_array(
+                            &schedule
+                                .skip_day_list
+                                .iter()
+                                .map(|e| e.to_string())
+                                .collect::<Vec<String>>()
+                        ),
+                    ],
+                )
+                .unwrap(),
+            None => self
+                .db_connection
+                .execute(r#"DELETE FROM "schedules" WHERE "url" = ?"#, [url])
+                .unwrap(),
+        };
+    }
+
+    pub fn select_schedule(&self, url: &str) -> Schedule {
+        self.db_connection
+            .prepare(
+                r#"SELECT "next_fetch_date", "skip_hours", "skip_days" FROM "schedules" WHERE "url" = ?"#,
+            )
+            .unwrap()
+            .query_map([url], |r| {
+                Ok((
+                    r.get::<_, DateTime<Utc>>(0)?,
+                    r.get::<_, Option<String>>(1)?,
+                    r.get::<_, Option<String>>(2)?,
+                ))
+            })
+            .unwrap()
+            .map(|e| e.unwrap())
+            .next()
+            .map(|(next_fetch_date, skip_hours, skip_days)| {
+                let parse = |s: Option<String>| -> JsonValue {
+                    s.map_or(JsonValue::new_array(), |s| json::parse(&s).unwrap())
+                };
+                Schedule {
+                    next_fetch_date: Some(next_fetch_date),
+                    skip_hour_list: parse(skip_hours)
+                        .members()
+                        .filter_map(|e| e.as_u32())
+                        .collect(),
+                    skip_day_list: parse(skip_days)
+                        .members()
+                        .filter_map(|e| e.as_str().and_then(schedule::parse_weekday))
+                        .collect(),
+                }
+            })
+            .unwrap_or_default()
+    }
+}
+
+//feed ids
+impl Database {
+    pub fn upsert_feed_id(&self, url: &str, id: &str) {
+        self.db_connection
+            .execute(
+                r#"
+                    INSERT OR REPLACE INTO "feed_ids"
+                    ("update_date", "url", "id")
+                    VALUES (CURRENT_TIMESTAMP, ?, ?)
+                "#,
+                params![url, id],
+            )
+            .unwrap();
+    }
+
+    pub fn select_feed_id(&self, url: &str) -> Option<String> {
+        self.db_connection
+            .prepare(r#"SELECT "id" FROM "feed_ids" WHERE "url" = ?"#)
+            .unwrap()
+            .query_map([url], |r| r.get(0))
+            .unwrap()
+            .map(|e| e.unwrap())
+            .next()
+    }
+}
+
+//discoveries
+impl Database {
+    pub fn upsert_discovery(&self, url: &str, feed_url: &str) {
+        self.db_connection
+            .execute(
+                r#"
+                    INSERT OR REPLACE INTO "discoveries"
+                    ("update_date", "url", "feed_url")
+                    VALUES (?, ?, ?)
+                "#,
+                params![Utc::now(), url, feed_url],
+            )
+            .unwrap();
+    }
+
+    //Returns `(feed_url, update_date)`.
+    pub fn select_discovery(&self, url: &str) -> Option<(String, DateTime<Utc>)> {
+        self.db_connection
+            .prepare(r#"SELECT "feed_url", "update_date" FROM "discoveries" WHERE "url" = ?"#)
+            .unwrap()
+            .query_map([url], |r| Ok((r.get(0)?, r.get(1)?)))
+            .unwrap()
+            .map(|e| e.unwrap())
+            .next()
+    }
+
+    pub fn delete_discovery(&self, url: &str) {
+        self.db_connection
+            .execute(r#"DELETE FROM "discoveries" WHERE "url" = ?"#, [url])
+            .unwrap();
+    }
+}
+
+//health
+impl Database {
+    pub fn insert_fetch_attempt(&self, fetch_attempt: &FetchAttempt) {
+        self.db_connection
+            .execute(
+                r#"
+                    INSERT INTO "fetch_attempts"
+                    ("date", "url", "status", "duration_ms", "error", "size", "item_count")
+                    VALUES (?, ?, ?, ?, ?, ?, ?)
+                "#,
+                params![
+                    fetch_attempt.date,
+                    fetch_attempt.url,
+                    fetch_attempt.status,
+                    fetch_attempt.duration.as_millis() as i64,
+                    fetch_attempt.error,
+                    fetch_attempt.size.map(|e| e as i64),
+                    fetch_attempt.item_count.map(|e| e as i64)
+                ],
+            )
+            .unwrap();
+    }
+
+    pub fn delete_fetch_attempts_before(&self, date: &DateTime<Utc>) {
+        self.db_connection
+            .execute(r#"DELETE FROM "fetch_attempts" WHERE "date" < ?"#, [date])
+            .unwrap();
+    }
+
+    //Returns `(attempt_count, failure_count, average_duration_ms)` of the recorded fetch attempts of the feed URL.
+    pub fn select_fetch_attempt_stats(&self, url: &str) -> (usize, usize, Option<f64>) {
+        self.db_connection
+            .query_row(
+                r#"
+                    SELECT COUNT(*), COUNT("error"), AVG("duration_ms")
+                    FROM "fetch_attempts" WHERE "url" = ?
+                "#,
+                [url],
+                |r| Ok((r.get(0)?, r.get(1)?, r.get(2)?)),
+            )
+            .unwrap()
+    }
+
+    pub fn upsert_feed_health(&self, feed_health: &FeedHealth) {
+        self.db_connection
+            .execute(
+                r#"
+                    INSERT OR REPLACE INTO "feed_health"
+                    ("url", "consecutive_failure_count", "last_success_date", "last_attempt_date", "last_error", "is_unhealthy", "paused_date")
+                    VALUES (?, ?, ?, ?, ?, ?, ?)
+                "#,
+                params![
+                    feed_health.url,
+                    feed_health.consecutive_failure_count as i64,
+                    feed_health.last_success_date,
+                    feed_health.last_attempt_date,
+                    feed_health.last_error,
+                    feed_health.is_unhealthy,
+                    feed_health.paused_date
+                ],
+            )
+            .unwrap();
+    }
+
+    pub fn select_feed_health(&self, url: &str) -> Option<FeedHealth> {
+        self.db_connection
+            .prepare(
+                r#"
+                    SELECT "url", "consecutive_failure_count", "last_success_date", "last_attempt_date", "last_error", "is_unhealthy", "paused_date"
+                    FROM "feed_health" WHERE "url" = ?
+                "#,
+            )
+            .unwrap()
+            .query_map([url], |r| {
+                Ok(FeedHealth {
+                    url: r.get(0)?,
+                    consecutive_failure_count: r.get::<_, i64>(1)? as usize,
+                    last_success_date: r.get(2)?,
+                    last_attempt_date: r.get(3)?,
+                    last_error: r.get(4)?,
+                    is_unhealthy: r.get(5)?,
+                    paused_date: r.get(6)?,
+                })
+            })
+            .unwrap()
+            .map(|e| e.unwrap())
+            .next()
+    }
+}
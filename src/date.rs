@@ -0,0 +1,255 @@
+use chrono::{DateTime, FixedOffset, NaiveDate, NaiveDateTime, TimeZone, Utc};
+
+//Feeds in the wild often use timezone abbreviations which are not allowed by RFC 2822 nor RFC 3339.
+const TIMEZONE_ABBREVIATION_LIST: [(&str, &str); 20] = [
+    ("Z", "+0000"),
+    ("UT", "+0000"),
+    ("UTC", "+0000"),
+    ("GMT", "+0000"),
+    ("EST", "-0500"),
+    ("EDT", "-0400"),
+    ("CST", "-0600"),
+    ("CDT", "-0500"),
+    ("MST", "-0700"),
+    ("MDT", "-0600"),
+    ("PST", "-0800"),
+    ("PDT", "-0700"),
+    ("BST", "+0100"),
+    ("CET", "+0100"),
+    ("CEST", "+0200"),
+    ("IST", "+0530"),
+    ("JST", "+0900"),
+    ("KST", "+0900"),
+    ("AEST", "+1000"),
+    ("AEDT", "+1100"),
+];
+
+const WEEKDAY_LIST: [&str; 7] = [
+    "monday",
+    "tuesday",
+    "wednesday",
+    "thursday",
+    "friday",
+    "saturday",
+    "sunday",
+];
+
+const MONTH_LIST: [&str; 12] = [
+    "january",
+    "february",
+    "march",
+    "april",
+    "may",
+    "june",
+    "july",
+    "august",
+    "september",
+    "october",
+    "november",
+    "december",
+];
+
+const FORMAT_WITH_OFFSET_LIST: [&str; 7] = [
+    "%d %b %Y %H:%M:%S %z",
+    "%d %b %Y %H:%M %z",
+    "%b %d %Y %H:%M:%S %z",
+    "%Y-%m-%dT%H:%M:%S%.f%z",
+    "%Y-%m-%d %H:%M:%S%.f%z",
+    "%Y-%m-%d %H:%M:%S%.f %z",
+    "%Y/%m/%d %H:%M:%S %z",
+];
+
+const FORMAT_WITHOUT_OFFSET_LIST: [&str; 8] = [
+    "%d %b %Y %H:%M:%S",
+    "%d %b %Y %H:%M",
+    "%b %d %Y %H:%M:%S",
+    "%Y-%m-%dT%H:%M:%S%.f",
+    "%Y-%m-%d %H:%M:%S%.f",
+    "%Y-%m-%dT%H:%M",
+    "%Y-%m-%d %H:%M",
+    "%Y/%m/%d %H:%M:%S",
+];
+
+const DATE_ONLY_FORMAT_LIST: [&str; 4] = ["%Y-%m-%d", "%Y/%m/%d", "%d %b %Y", "%b %d %Y"];
+
+//Parses a date of RSS `<pubDate>` (RFC 2822) or Atom `<updated>`/`<published>` (RFC 3339).
+//Commonly seen malformed variants (wrong weekdays, full month names, timezone abbreviations, missing timezones, etc.) are also accepted.
+//A date without timezone is regarded as UTC.
+pub fn parse(s: &str) -> Option<DateTime<Utc>> {
+    let s: String = s.split_whitespace().collect::<Vec<&str>>().join(" ");
+    if s.is_empty() {
+        return None;
+    }
+
+    if let Ok(d) = DateTime::parse_from_rfc3339(&s) {
+        return Some(d.with_timezone(&Utc));
+    }
+    if let Ok(d) = DateTime::parse_from_rfc2822(&s) {
+        return Some(d.with_timezone(&Utc));
+    }
+
+    let s: String = normalize(&s);
+
+    if let Ok(d) = DateTime::parse_from_rfc2822(&s) {
+        return Some(d.with_timezone(&Utc));
+    }
+    for format in FORMAT_WITH_OFFSET_LIST {
+        if let Ok(d) = DateTime::<FixedOffset>::parse_from_str(&s, format) {
+            return Some(d.with_timezone(&Utc));
+        }
+    }
+    for format in FORMAT_WITHOUT_OFFSET_LIST {
+        if let Ok(d) = NaiveDateTime::parse_from_str(&s, format) {
+            return Some(Utc.from_utc_datetime(&d));
+        }
+    }
+    for format in DATE_ONLY_FORMAT_LIST {
+        if let Ok(d) = NaiveDate::parse_from_str(&s, format) {
+            return Some(Utc.from_utc_datetime(&d.and_hms_opt(0, 0, 0).unwrap()));
+        }
+    }
+
+    None
+}
+
+//Drops weekdays (which are sometimes inconsistent with the dates), abbreviates month names
+// and replaces timezone abbreviations with numeric offsets.
+fn normalize(s: &str) -> String {
+    s.split(' ')
+        .map(|token| token.trim_end_matches(','))
+        .filter(|token| {
+            let lower = token.to_lowercase();
+            !WEEKDAY_LIST
+                .iter()
+                .any(|w| *w == lower || (lower.len() == 3 && w.starts_with(&lower)))
+        })
+        .map(|token| {
+            let lower = token.to_lowercase();
+            if let Some(month) = MONTH_LIST
+                .iter()
+                .find(|m| **m == lower || (lower.len() >= 3 && m.starts_with(&lower)))
+            {
+                return month[..1].to_uppercase() + &month[1..3];
+            }
+            let upper = token.to_uppercase();
+            if let Some((_, offset)) = TIMEZONE_ABBREVIATION_LIST
+                .iter()
+                .find(|(abbreviation, _)| *abbreviation == upper)
+            {
+                return offset.to_string();
+            }
+            token.to_string()
+        })
+        .collect::<Vec<String>>()
+        .join(" ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn utc(s: &str) -> Option<DateTime<Utc>> {
+        Some(DateTime::parse_from_rfc3339(s).unwrap().with_timezone(&Utc))
+    }
+
+    #[test]
+    fn test_standard_formats() {
+        assert_eq!(
+            utc("2023-01-02T03:04:05Z"),
+            parse("Mon, 02 Jan 2023 03:04:05 +0000")
+        );
+        assert_eq!(
+            utc("2023-01-02T03:04:05Z"),
+            parse("Mon, 02 Jan 2023 12:04:05 +0900")
+        );
+        assert_eq!(
+            utc("2023-01-02T03:04:05Z"),
+            parse("2023-01-02T12:04:05+09:00")
+        );
+        assert_eq!(
+            utc("2023-01-02T03:04:05.5Z"),
+            parse(" 2023-01-02T03:04:05.5Z\n")
+        );
+    }
+
+    #[test]
+    fn test_malformed_rfc_2822() {
+        //wrong weekday
+        assert_eq!(
+            utc("2023-01-02T03:04:05Z"),
+            parse("Fri, 02 Jan 2023 03:04:05 +0000")
+        );
+        //full names
+        assert_eq!(
+            utc("2023-01-02T03:04:05Z"),
+            parse("Monday, 02 January 2023 03:04:05 +0000")
+        );
+        //timezone abbreviations
+        assert_eq!(
+            utc("2023-01-02T03:04:05Z"),
+            parse("Mon, 02 Jan 2023 12:04:05 JST")
+        );
+        assert_eq!(
+            utc("2023-01-02T03:04:05Z"),
+            parse("Sun, 01 Jan 2023 22:04:05 est")
+        );
+        assert_eq!(
+            utc("2023-01-02T03:04:05Z"),
+            parse("Mon, 02 Jan 2023 03:04:05 UTC")
+        );
+        //without seconds
+        assert_eq!(
+            utc("2023-01-02T03:04:00Z"),
+            parse("Mon, 02 Jan 2023 03:04 GMT")
+        );
+        //month first
+        assert_eq!(
+            utc("2023-01-02T03:04:05Z"),
+            parse("Jan 02 2023 03:04:05 +0000")
+        );
+    }
+
+    #[test]
+    fn test_other_formats() {
+        assert_eq!(
+            utc("2023-01-02T03:04:05Z"),
+            parse("2023-01-02 12:04:05+0900")
+        );
+        assert_eq!(
+            utc("2023-01-02T03:04:05Z"),
+            parse("2023-01-02 12:04:05 +0900")
+        );
+        assert_eq!(
+            utc("2023-01-02T03:04:05Z"),
+            parse("2023/01/02 12:04:05 +0900")
+        );
+    }
+
+    #[test]
+    fn test_without_timezone() {
+        assert_eq!(utc("2023-01-02T03:04:05Z"), parse("2023-01-02T03:04:05"));
+        assert_eq!(
+            utc("2023-01-02T03:04:05Z"),
+            parse("Mon, 02 Jan 2023 03:04:05")
+        );
+        assert_eq!(utc("2023-01-02T03:04:00Z"), parse("2023-01-02 03:04"));
+        assert_eq!(utc("2023-01-02T03:04:05Z"), parse("2023/01/02 03:04:05"));
+    }
+
+    #[test]
+    fn test_date_only() {
+        assert_eq!(utc("2023-01-02T00:00:00Z"), parse("2023-01-02"));
+        assert_eq!(utc("2023-01-02T00:00:00Z"), parse("2023/01/02"));
+        assert_eq!(utc("2023-01-02T00:00:00Z"), parse("02 Jan 2023"));
+        assert_eq!(utc("2023-01-02T00:00:00Z"), parse("January 2, 2023"));
+    }
+
+    #[test]
+    fn test_invalid() {
+        assert_eq!(None, parse(""));
+        assert_eq!(None, parse("   "));
+        assert_eq!(None, parse("yesterday"));
+        assert_eq!(None, parse("2023-13-02"));
+        assert_eq!(None, parse("Mon, 32 Jan 2023 03:04:05 +0000"));
+    }
+}
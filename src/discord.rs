@@ -1,23 +1,41 @@
 use std::error::Error;
-use std::time::Duration;
 
 use json::{self, JsonValue};
 use log::*;
 use reqwest::blocking::{Client, Response};
 
+use super::http;
+use super::http::HttpOptions;
+use super::template::Template;
+use super::template::TemplateContext;
 use super::trigger::Trigger;
 use super::trigger::TriggerInfo;
 
+pub const DEFAULT_TEMPLATE: &str = "--------------------\nTitle: {{ title }}\nLink: {{ link }}\nDate: {{ pub_date }}{% if media %}\nMedia: {{ media }}{% endif %}";
+
 pub struct DiscordNotification {
+    //name of the trigger in the config, used to look up `templates` of feeds
+    name: String,
+    template: Template,
     client: Client,
     webhook_url: String,
+    http_options: HttpOptions,
 }
 
 impl DiscordNotification {
-    pub fn new(webhook_url: &str) -> Self {
+    //Only the proxy and the TLS settings, `user_agent` and `timeout` of `http_options` are used.
+    pub fn new(
+        name: &str,
+        template: Template,
+        webhook_url: &str,
+        http_options: &HttpOptions,
+    ) -> Self {
         DiscordNotification {
-            client: Client::new(),
+            name: name.to_string(),
+            template,
+            client: http::get_client(&http_options.client_options).unwrap(),
             webhook_url: String::from(webhook_url),
+            http_options: http_options.clone(),
         }
     }
 }
@@ -26,27 +44,30 @@ impl Trigger for DiscordNotification {
     fn pull_trigger(&self, trigger_info: &TriggerInfo) -> Result<(), Box<dyn Error>> {
         debug!("DiscordNotification start: {:?}", trigger_info);
 
-        let content = format!(
-            "--------------------\nTitle: {}\nLink: {}\nDate: {}",
-            trigger_info.get_title().as_ref().unwrap_or(&String::new()),
-            trigger_info.get_link().as_ref().unwrap_or(&String::new()),
-            trigger_info
-                .get_pub_date()
-                .as_ref()
-                .unwrap_or(&String::new()),
-        );
-
-        let json: JsonValue = json::object! {
+        let content: String = trigger_info
+            .get_template(&self.name)
+            .unwrap_or(&self.template)
+            .render(&TemplateContext::from(trigger_info));
+
+        let mut json: JsonValue = json::object! {
             wait: true,
             content: content
         };
+        if let Some(thumbnail) = trigger_info.get_thumbnail() {
+            json["embeds"] = json::array![json::object! {
+                image: json::object! {
+                    url: thumbnail.clone()
+                }
+            }];
+        }
 
         let res: Response = self
             .client
             .post(&self.webhook_url)
             .body(json.dump())
             .header("Content-Type", "application/json")
-            .timeout(Duration::from_millis(10000))
+            .header("User-Agent", &self.http_options.user_agent)
+            .timeout(self.http_options.timeout)
             .send()?;
 
         if res.status().is_success() {
@@ -0,0 +1,81 @@
+use scraper::{ElementRef, Html, Selector};
+
+use super::urlutil;
+
+//A feed advertised by an HTML page via `<link rel="alternate" type="application/rss+xml" href="...">`.
+#[derive(Debug, Clone)]
+pub struct FeedCandidate {
+    url: String,
+    mime_type: String,
+    title: Option<String>,
+}
+
+impl FeedCandidate {
+    pub fn get_url(&self) -> &str {
+        &self.url
+    }
+
+    pub fn get_mime_type(&self) -> &str {
+        &self.mime_type
+    }
+
+    pub fn get_title(&self) -> &Option<String> {
+        &self.title
+    }
+}
+
+//`application/rdf+xml` (RSS 1.0) is not listed as it cannot be parsed.
+//`application/xml` may be anything, so the candidates are checked when they are fetched.
+const FEED_MIME_TYPE_LIST: [&str; 3] = [
+    "application/rss+xml",
+    "application/atom+xml",
+    "application/xml",
+];
+
+//Lists the feeds advertised by an HTML page in the document order (which usually puts the main feed first).
+//`page_url` is used to resolve relative URLs unless the page has `<base href="...">`.
+pub fn discover(html: &str, page_url: &str) -> Vec<FeedCandidate> {
+    let document = Html::parse_document(html);
+
+    let base_url: String = document
+        .select(&Selector::parse("base[href]").unwrap())
+        .next()
+        .and_then(|e| e.value().attr("href"))
+        .map(|href| urlutil::resolve(Some(page_url), href))
+        .unwrap_or_else(|| page_url.to_string());
+
+    let mut ret: Vec<FeedCandidate> = Vec::new();
+    for e in document.select(&Selector::parse("link[rel][type][href]").unwrap()) {
+        if !is_alternate(&e) {
+            continue;
+        }
+        let mime_type: String = e.value().attr("type").unwrap().trim().to_ascii_lowercase();
+        if !FEED_MIME_TYPE_LIST.contains(&mime_type.as_str()) {
+            continue;
+        }
+        let url: String = urlutil::resolve(Some(&base_url), e.value().attr("href").unwrap().trim());
+        //Some pages list the same feed twice (e.g. in `<head>` and `<body>`).
+        if ret.iter().any(|c| c.url == url) {
+            continue;
+        }
+        ret.push(FeedCandidate {
+            url,
+            mime_type,
+            title: e
+                .value()
+                .attr("title")
+                .map(|s| s.trim().to_string())
+                .filter(|s| !s.is_empty()),
+        });
+    }
+    ret
+}
+
+//`rel` is a space-separated list of keywords (e.g. `rel="alternate home"`).
+fn is_alternate(e: &ElementRef) -> bool {
+    e.value()
+        .attr("rel")
+        .unwrap()
+        .split_ascii_whitespace()
+        .any(|r| r.eq_ignore_ascii_case("alternate"))
+}
@@ -0,0 +1,43 @@
+use encoding_rs::{Encoding, UTF_8};
+use log::*;
+use regex::bytes;
+use regex::Regex;
+
+//Decodes a feed into a string.
+//The encoding is determined in this order (cf. RFC 7303):
+// 1. byte order mark
+// 2. `charset` parameter of `Content-Type` HTTP header
+// 3. `encoding` pseudo-attribute of XML declaration (e.g. `<?xml version="1.0" encoding="Shift_JIS"?>`)
+// 4. UTF-8
+pub fn decode(bytes: &[u8], content_type: Option<&str>) -> String {
+    let encoding: &'static Encoding = Encoding::for_bom(bytes)
+        .map(|(encoding, _)| encoding)
+        .or_else(|| content_type.and_then(get_charset_from_content_type))
+        .or_else(|| get_encoding_from_xml_declaration(bytes))
+        .unwrap_or(UTF_8);
+
+    //`decode()` also strips BOM.
+    let (s, _, had_errors) = encoding.decode(bytes);
+    if had_errors {
+        warn!(
+            "Some characters could not be decoded as {}.",
+            encoding.name()
+        );
+    }
+    s.to_string()
+}
+
+fn get_charset_from_content_type(content_type: &str) -> Option<&'static Encoding> {
+    Regex::new(r#"(?i)charset\s*=\s*"?([^";\s]+)"#)
+        .unwrap()
+        .captures(content_type)
+        .and_then(|c| Encoding::for_label(c[1].as_bytes()))
+}
+
+fn get_encoding_from_xml_declaration(bytes: &[u8]) -> Option<&'static Encoding> {
+    let head: &[u8] = &bytes[..bytes.len().min(1024)];
+    bytes::Regex::new(r#"^\s*<\?xml[^>]*?encoding\s*=\s*["']([A-Za-z0-9._:-]+)["']"#)
+        .unwrap()
+        .captures(head)
+        .and_then(|c| Encoding::for_label(&c[1]))
+}
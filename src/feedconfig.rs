@@ -1,8 +1,63 @@
+use std::collections::HashMap;
+use std::time::Duration;
+
+use super::health;
+use super::http::HttpOptions;
+use super::scrape::ScrapeConfig;
+use super::template::Template;
+use super::urlutil::UrlCanonicalization;
+
+//Determines what identifies a feed item.
+//This only applies to RSS feeds as Atom entries always have `<id>`.
+#[derive(Debug, Clone)]
+pub enum IdentityStrategy {
+    //`<guid>` when present, and `Legacy` otherwise.
+    Guid,
+    //`<link>` when present, and `Legacy` otherwise.
+    Link,
+    //The combination of title, link and publish date.
+    Legacy,
+}
+
+impl IdentityStrategy {
+    pub fn new(s: &str) -> Self {
+        match s {
+            "guid" => Self::Guid,
+            "link" => Self::Link,
+            "legacy" => Self::Legacy,
+            _ => panic!("Unknown identity strategy: {}", s),
+        }
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct FeedConfig {
     url: String,
     pub should_omit_date_field_from_hash: bool,
     pub is_golang_blog_mode: bool,
+    pub max_entry_age: Option<Duration>,
+    pub flood_threshold: Option<usize>,
+    pub should_send_flood_summary: bool,
+    pub identity_strategy: IdentityStrategy,
+    pub is_lenient_mode: bool,
+    //`None` disables URL canonicalization.
+    pub url_canonicalization: Option<UrlCanonicalization>,
+    pub http_options: HttpOptions,
+    pub should_update_url_on_permanent_redirect: bool,
+    //When `Some`, `url` is regarded as an HTML page to scrape rather than a feed.
+    pub scrape_config: Option<ScrapeConfig>,
+    //When `Some`, the stdout of this command (and its arguments) is used as the contents instead of fetching `url`.
+    pub exec_command: Option<Vec<String>>,
+    //Subscribes to the hub of the feed when `websub` is configured.
+    pub should_use_websub: bool,
+    //Skips fetches until the time hinted by the feed (e.g. `<ttl>`) or the server (e.g. `Retry-After`).
+    pub should_honor_update_hints: bool,
+    //An alert is raised when the feed fails this many times in a row.
+    pub unhealthy_threshold: Option<usize>,
+    //The feed is paused when it fails this many times in a row.
+    pub pause_threshold: Option<usize>,
+    //Message templates which override the ones of the triggers, keyed by trigger name.
+    pub template_map: HashMap<String, Template>,
 }
 
 impl FeedConfig {
@@ -11,6 +66,22 @@ impl FeedConfig {
             url: url.to_string(),
             should_omit_date_field_from_hash: false,
             is_golang_blog_mode: false,
+            max_entry_age: None,
+            flood_threshold: None,
+            should_send_flood_summary: false,
+            //`Guid` would make all the items stored by older versions look new.
+            identity_strategy: IdentityStrategy::Legacy,
+            is_lenient_mode: false,
+            url_canonicalization: None,
+            http_options: HttpOptions::new(),
+            should_update_url_on_permanent_redirect: false,
+            scrape_config: None,
+            exec_command: None,
+            should_use_websub: true,
+            should_honor_update_hints: false,
+            unhealthy_threshold: Some(health::DEFAULT_UNHEALTHY_THRESHOLD),
+            pause_threshold: None,
+            template_map: HashMap::new(),
         }
     }
 
@@ -1,3 +1,7 @@
+use std::sync::OnceLock;
+
+use regex::Regex;
+
 pub enum FeedType {
     Rss,
     Atom,
@@ -6,9 +10,18 @@ pub enum FeedType {
 
 impl FeedType {
     pub fn new(xml: &str) -> Self {
-        if xml.contains("</rss>") {
+        static RSS_REGEX: OnceLock<Regex> = OnceLock::new();
+        static ATOM_REGEX: OnceLock<Regex> = OnceLock::new();
+        //The root element may be prefixed (e.g. `<a:feed xmlns:a="http://www.w3.org/2005/Atom">`).
+        if RSS_REGEX
+            .get_or_init(|| Regex::new(r"</([\w.-]+:)?rss\s*>").unwrap())
+            .is_match(xml)
+        {
             Self::Rss
-        } else if xml.contains("</feed>") {
+        } else if ATOM_REGEX
+            .get_or_init(|| Regex::new(r"</([\w.-]+:)?feed\s*>").unwrap())
+            .is_match(xml)
+        {
             Self::Atom
         } else {
             Self::Unknown
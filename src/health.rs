@@ -0,0 +1,160 @@
+use std::time::Duration;
+
+use chrono::{DateTime, Utc};
+
+use super::config::Config;
+use super::database::Database;
+use super::feedconfig::FeedConfig;
+
+pub const DEFAULT_UNHEALTHY_THRESHOLD: usize = 5;
+
+//A paused feed is still fetched at this interval so that it is resumed once it recovers.
+const PAUSED_FEED_RETRY_INTERVAL: Duration = Duration::from_secs(24 * 60 * 60);
+
+//Fetch attempts older than this are deleted.
+const FETCH_ATTEMPT_RETENTION: Duration = Duration::from_secs(30 * 24 * 60 * 60);
+
+//A fetch of a feed, whether it succeeded or not.
+#[derive(Debug, Clone)]
+pub struct FetchAttempt {
+    //`url` in the config
+    pub url: String,
+    pub date: DateTime<Utc>,
+    //HTTP status; `None` when no response was received or the source is not HTTP
+    pub status: Option<u16>,
+    pub duration: Duration,
+    //`None` on success
+    pub error: Option<String>,
+    //in bytes
+    pub size: Option<usize>,
+    pub item_count: Option<usize>,
+}
+
+//Summary of the recent fetch attempts of a feed.
+#[derive(Debug, Clone)]
+pub struct FeedHealth {
+    //`url` in the config
+    pub url: String,
+    pub consecutive_failure_count: usize,
+    pub last_success_date: Option<DateTime<Utc>>,
+    pub last_attempt_date: DateTime<Utc>,
+    pub last_error: Option<String>,
+    //whether an alert has been raised for the ongoing failures
+    pub is_unhealthy: bool,
+    //when the feed was paused; `None` when it is active
+    pub paused_date: Option<DateTime<Utc>>,
+}
+
+impl FeedHealth {
+    fn new(url: &str) -> Self {
+        FeedHealth {
+            url: url.to_string(),
+            consecutive_failure_count: 0,
+            last_success_date: None,
+            last_attempt_date: Utc::now(),
+            last_error: None,
+            is_unhealthy: false,
+            paused_date: None,
+        }
+    }
+}
+
+//Whether the feed is paused due to consecutive failures and should not be fetched now.
+pub fn is_paused(db: &Database, feed_config: &FeedConfig) -> bool {
+    match db.select_feed_health(feed_config.get_url()) {
+        Some(FeedHealth {
+            paused_date: Some(_),
+            last_attempt_date,
+            ..
+        }) => {
+            Utc::now() - last_attempt_date
+                < chrono::Duration::from_std(PAUSED_FEED_RETRY_INTERVAL).unwrap()
+        }
+        _ => false,
+    }
+}
+
+pub fn delete_old_fetch_attempts(db: &Database) {
+    db.delete_fetch_attempts_before(
+        &(Utc::now() - chrono::Duration::from_std(FETCH_ATTEMPT_RETENTION).unwrap()),
+    );
+}
+
+//Records a fetch attempt, and raises an alert when the feed becomes unhealthy, is paused or recovers.
+pub fn record_fetch_attempt(
+    db: &Database,
+    config: &Config,
+    feed_config: &FeedConfig,
+    fetch_attempt: &FetchAttempt,
+) {
+    db.insert_fetch_attempt(fetch_attempt);
+
+    let url: &str = feed_config.get_url();
+    let mut health: FeedHealth = db
+        .select_feed_health(url)
+        .unwrap_or_else(|| FeedHealth::new(url));
+    health.last_attempt_date = fetch_attempt.date;
+
+    match &fetch_attempt.error {
+        None => {
+            if health.is_unhealthy || health.paused_date.is_some() {
+                super::raise_alert(
+                    config,
+                    "Feed recovered",
+                    url,
+                    &format!(
+                        "{} has been fetched successfully after {} consecutive failure(s).",
+                        url, health.consecutive_failure_count
+                    ),
+                );
+            }
+            health.consecutive_failure_count = 0;
+            health.last_success_date = Some(fetch_attempt.date);
+            health.last_error = None;
+            health.is_unhealthy = false;
+            health.paused_date = None;
+        }
+        Some(error) => {
+            health.consecutive_failure_count += 1;
+            health.last_error = Some(error.clone());
+            let since: String = match health.last_success_date {
+                Some(d) => format!("since {}", d.to_rfc3339()),
+                None => "ever".to_string(),
+            };
+            if !health.is_unhealthy
+                && feed_config
+                    .unhealthy_threshold
+                    .is_some_and(|t| health.consecutive_failure_count >= t)
+            {
+                health.is_unhealthy = true;
+                super::raise_alert(
+                    config,
+                    "Feed unhealthy",
+                    url,
+                    &format!(
+                        "{} has failed {} time(s) in a row without success {}: {}",
+                        url, health.consecutive_failure_count, since, error
+                    ),
+                );
+            }
+            if health.paused_date.is_none()
+                && feed_config
+                    .pause_threshold
+                    .is_some_and(|t| health.consecutive_failure_count >= t)
+            {
+                health.paused_date = Some(fetch_attempt.date);
+                super::raise_alert(
+                    config,
+                    "Feed paused",
+                    url,
+                    &format!(
+                        "{} has been paused after {} consecutive failures; it will be retried once a day: {}",
+                        url, health.consecutive_failure_count, error
+                    ),
+                );
+            }
+        }
+    }
+
+    db.upsert_feed_health(&health);
+}
@@ -0,0 +1,222 @@
+use std::sync::OnceLock;
+
+use regex::{Captures, Regex};
+
+//HTML named character references commonly seen in feeds.
+//This is not the complete list (which has more than 2000 entries) but covers almost all the real-world cases.
+pub const NAMED_ENTITY_LIST: &[(&str, char)] = &[
+    ("quot", '"'),
+    ("amp", '&'),
+    ("apos", '\''),
+    ("lt", '<'),
+    ("gt", '>'),
+    ("nbsp", '\u{00A0}'),
+    ("iexcl", '¡'),
+    ("cent", '¢'),
+    ("pound", '£'),
+    ("curren", '¤'),
+    ("yen", '¥'),
+    ("brvbar", '¦'),
+    ("sect", '§'),
+    ("uml", '¨'),
+    ("copy", '©'),
+    ("ordf", 'ª'),
+    ("laquo", '«'),
+    ("not", '¬'),
+    ("shy", '\u{00AD}'),
+    ("reg", '®'),
+    ("macr", '¯'),
+    ("deg", '°'),
+    ("plusmn", '±'),
+    ("sup2", '²'),
+    ("sup3", '³'),
+    ("acute", '´'),
+    ("micro", 'µ'),
+    ("para", '¶'),
+    ("middot", '·'),
+    ("cedil", '¸'),
+    ("sup1", '¹'),
+    ("ordm", 'º'),
+    ("raquo", '»'),
+    ("frac14", '¼'),
+    ("frac12", '½'),
+    ("frac34", '¾'),
+    ("iquest", '¿'),
+    ("Agrave", 'À'),
+    ("Aacute", 'Á'),
+    ("Acirc", 'Â'),
+    ("Atilde", 'Ã'),
+    ("Auml", 'Ä'),
+    ("Aring", 'Å'),
+    ("AElig", 'Æ'),
+    ("Ccedil", 'Ç'),
+    ("Egrave", 'È'),
+    ("Eacute", 'É'),
+    ("Ecirc", 'Ê'),
+    ("Euml", 'Ë'),
+    ("Igrave", 'Ì'),
+    ("Iacute", 'Í'),
+    ("Icirc", 'Î'),
+    ("Iuml", 'Ï'),
+    ("ETH", 'Ð'),
+    ("Ntilde", 'Ñ'),
+    ("Ograve", 'Ò'),
+    ("Oacute", 'Ó'),
+    ("Ocirc", 'Ô'),
+    ("Otilde", 'Õ'),
+    ("Ouml", 'Ö'),
+    ("times", '×'),
+    ("Oslash", 'Ø'),
+    ("Ugrave", 'Ù'),
+    ("Uacute", 'Ú'),
+    ("Ucirc", 'Û'),
+    ("Uuml", 'Ü'),
+    ("Yacute", 'Ý'),
+    ("THORN", 'Þ'),
+    ("szlig", 'ß'),
+    ("agrave", 'à'),
+    ("aacute", 'á'),
+    ("acirc", 'â'),
+    ("atilde", 'ã'),
+    ("auml", 'ä'),
+    ("aring", 'å'),
+    ("aelig", 'æ'),
+    ("ccedil", 'ç'),
+    ("egrave", 'è'),
+    ("eacute", 'é'),
+    ("ecirc", 'ê'),
+    ("euml", 'ë'),
+    ("igrave", 'ì'),
+    ("iacute", 'í'),
+    ("icirc", 'î'),
+    ("iuml", 'ï'),
+    ("eth", 'ð'),
+    ("ntilde", 'ñ'),
+    ("ograve", 'ò'),
+    ("oacute", 'ó'),
+    ("ocirc", 'ô'),
+    ("otilde", 'õ'),
+    ("ouml", 'ö'),
+    ("divide", '÷'),
+    ("oslash", 'ø'),
+    ("ugrave", 'ù'),
+    ("uacute", 'ú'),
+    ("ucirc", 'û'),
+    ("uuml", 'ü'),
+    ("yacute", 'ý'),
+    ("thorn", 'þ'),
+    ("yuml", 'ÿ'),
+    ("OElig", 'Œ'),
+    ("oelig", 'œ'),
+    ("Scaron", 'Š'),
+    ("scaron", 'š'),
+    ("Yuml", 'Ÿ'),
+    ("fnof", 'ƒ'),
+    ("circ", 'ˆ'),
+    ("tilde", '˜'),
+    ("ensp", '\u{2002}'),
+    ("emsp", '\u{2003}'),
+    ("thinsp", '\u{2009}'),
+    ("zwnj", '\u{200C}'),
+    ("zwj", '\u{200D}'),
+    ("lrm", '\u{200E}'),
+    ("rlm", '\u{200F}'),
+    ("ndash", '–'),
+    ("mdash", '—'),
+    ("lsquo", '‘'),
+    ("rsquo", '’'),
+    ("sbquo", '‚'),
+    ("ldquo", '“'),
+    ("rdquo", '”'),
+    ("bdquo", '„'),
+    ("dagger", '†'),
+    ("Dagger", '‡'),
+    ("bull", '•'),
+    ("hellip", '…'),
+    ("permil", '‰'),
+    ("euro", '€'),
+    ("trade", '™'),
+];
+
+pub fn find_named_entity(name: &str) -> Option<char> {
+    NAMED_ENTITY_LIST
+        .iter()
+        .find(|(n, _)| *n == name)
+        .map(|(_, c)| *c)
+}
+
+//Decodes both named and numeric character references.
+//Unknown references are left as they are.
+pub fn decode_entities(s: &str) -> String {
+    static ENTITY_REGEX: OnceLock<Regex> = OnceLock::new();
+    ENTITY_REGEX
+        .get_or_init(|| Regex::new(r"&(#[0-9]+|#[xX][0-9a-fA-F]+|[A-Za-z][A-Za-z0-9]*);").unwrap())
+        .replace_all(s, |c: &Captures| {
+            let name: &str = &c[1];
+            let decoded: Option<char> =
+                if let Some(hex) = name.strip_prefix("#x").or_else(|| name.strip_prefix("#X")) {
+                    u32::from_str_radix(hex, 16).ok().and_then(char::from_u32)
+                } else if let Some(dec) = name.strip_prefix('#') {
+                    dec.parse::<u32>().ok().and_then(char::from_u32)
+                } else {
+                    find_named_entity(name)
+                };
+            match decoded {
+                Some(decoded) => decoded.to_string(),
+                None => c[0].to_string(),
+            }
+        })
+        .to_string()
+}
+
+pub fn escape(s: &str) -> String {
+    s.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+        .replace('\'', "&#39;")
+}
+
+//Converts an HTML fragment to a plain text.
+//Block-level elements and `<br>` are converted to line breaks.
+pub fn to_plain_text(html: &str) -> String {
+    static INVISIBLE_REGEX: OnceLock<Regex> = OnceLock::new();
+    static LINE_BREAK_REGEX: OnceLock<Regex> = OnceLock::new();
+    static TAG_REGEX: OnceLock<Regex> = OnceLock::new();
+    static WHITESPACE_REGEX: OnceLock<Regex> = OnceLock::new();
+
+    let s: String = INVISIBLE_REGEX
+        .get_or_init(|| {
+            Regex::new(r"(?is)<!--.*?-->|<script\b.*?</script\s*>|<style\b.*?</style\s*>").unwrap()
+        })
+        .replace_all(html, "")
+        .to_string();
+    let s: String = LINE_BREAK_REGEX
+        .get_or_init(|| {
+            Regex::new(r"(?i)<br\s*/?>|</(p|div|li|h[1-6]|blockquote|pre|tr)\s*>").unwrap()
+        })
+        .replace_all(&s, "\n")
+        .to_string();
+    let s: String = TAG_REGEX
+        .get_or_init(|| Regex::new(r"<[^>]*>").unwrap())
+        .replace_all(&s, "")
+        .to_string();
+    let s: String = decode_entities(&s);
+
+    let whitespace_regex: &Regex =
+        WHITESPACE_REGEX.get_or_init(|| Regex::new(r"[ \t\r\u{00A0}]+").unwrap());
+    let line_list: Vec<String> = s
+        .split('\n')
+        .map(|l| whitespace_regex.replace_all(l, " ").trim().to_string())
+        .collect();
+
+    //Collapses consecutive empty lines.
+    let mut ret: Vec<String> = Vec::new();
+    for line in line_list {
+        if line.is_empty() && ret.last().is_none_or(|l| l.is_empty()) {
+            continue;
+        }
+        ret.push(line);
+    }
+    ret.join("\n").trim().to_string()
+}
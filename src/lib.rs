@@ -2,25 +2,55 @@ pub mod atom;
 pub mod command;
 pub mod config;
 pub mod database;
+pub mod date;
 pub mod discord;
+pub mod discovery;
+pub mod encoding;
 pub mod feedconfig;
 pub mod feedtype;
+pub mod health;
+pub mod html;
+pub mod http;
+pub mod media;
+pub mod namespace;
 pub mod rss;
+pub mod schedule;
+pub mod scrape;
+pub mod template;
 pub mod trigger;
 pub mod twitter;
+pub mod urlutil;
+pub mod webhook;
+pub mod websub;
+pub mod xmlutil;
 
 use std::env;
+use std::error::Error;
+use std::fs;
+use std::io;
+use std::io::Read;
+use std::path::PathBuf;
 use std::time::Duration;
 
+use chrono::{DateTime, Utc};
 use log::*;
-use reqwest::blocking::Client;
+use url::Url;
 
 use atom::Atom;
 use atom::AtomEntry;
+use config::Config;
 use database::Database;
+use discovery::FeedCandidate;
 use feedconfig::FeedConfig;
+use feedtype::FeedType;
+use http::HttpOptions;
+use http::HttpResponse;
+use http::Redirect;
+use http::StatusError;
 use rss::Rss;
 use rss::RssItem;
+use schedule::{Schedule, UpdateHints};
+use scrape::ScrapedPage;
 use trigger::Trigger;
 use trigger::TriggerInfo;
 
@@ -33,23 +63,514 @@ pub fn initialize_logger(should_log_debug: bool) {
     env_logger::init();
 }
 
-pub fn retrieve_xml(url: &str) -> String {
-    Client::new()
-        .get(url)
-        .timeout(Duration::from_millis(10000))
-        .send()
-        .unwrap()
-        .text()
-        .unwrap()
+//A discovered feed is used for this period before the HTML page is checked again,
+// so that a change of the advertised feed is followed.
+const DISCOVERY_MAX_AGE: Duration = Duration::from_secs(7 * 24 * 60 * 60);
+
+//A fetched feed document.
+pub struct RetrievedXml {
+    pub xml: String,
+    //the URL the contents were actually fetched from (i.e. after redirects)
+    pub url: String,
+    pub redirect_list: Vec<Redirect>,
+    //HTTP status; `None` for the other sources
+    pub status: Option<u16>,
+    //in bytes, before decoding
+    pub size: usize,
+    //`Cache-Control: max-age` and `Retry-After` of the HTTP response
+    pub update_hints: UpdateHints,
 }
 
-pub fn handle_rss_feed_case(
+pub fn retrieve_xml(feed_config: &FeedConfig) -> Result<RetrievedXml, Box<dyn Error>> {
+    match &feed_config.exec_command {
+        Some(exec_command) => retrieve_xml_from_command(feed_config.get_url(), exec_command),
+        None => retrieve_xml_from(feed_config.get_url(), &feed_config.http_options),
+    }
+}
+
+//`url` is either `-` (stdin), `file://...` or an HTTP(S) URL.
+fn retrieve_xml_from(
+    url: &str,
+    http_options: &HttpOptions,
+) -> Result<RetrievedXml, Box<dyn Error>> {
+    if url == "-" {
+        let mut bytes: Vec<u8> = Vec::new();
+        io::stdin().read_to_end(&mut bytes)?;
+        return Ok(RetrievedXml {
+            xml: encoding::decode(&bytes, None),
+            url: url.to_string(),
+            redirect_list: Vec::new(),
+            status: None,
+            size: bytes.len(),
+            update_hints: UpdateHints::default(),
+        });
+    }
+
+    if url.starts_with("file://") {
+        let path: PathBuf = Url::parse(url)?
+            .to_file_path()
+            .map_err(|_| format!("Invalid file URL: {}", url))?;
+        let bytes: Vec<u8> = fs::read(path)?;
+        return Ok(RetrievedXml {
+            xml: encoding::decode(&bytes, None),
+            url: url.to_string(),
+            redirect_list: Vec::new(),
+            status: None,
+            size: bytes.len(),
+            update_hints: UpdateHints::default(),
+        });
+    }
+
+    let response: HttpResponse = http::get(url, http_options)?;
+
+    //We don't use `Response::text()` as it only respects the HTTP header and ignores the XML declaration.
+    Ok(RetrievedXml {
+        xml: encoding::decode(&response.body, response.content_type.as_deref()),
+        url: response.url,
+        redirect_list: response.redirect_list,
+        status: Some(response.status),
+        size: response.body.len(),
+        update_hints: response.update_hints,
+    })
+}
+
+//The feed is the stdout of the command.
+//`url` is only used as the base URL of relative links.
+fn retrieve_xml_from_command(
+    url: &str,
+    exec_command: &[String],
+) -> Result<RetrievedXml, Box<dyn Error>> {
+    let (command, args) = exec_command.split_first().ok_or("`exec` is empty.")?;
+    let result = command::run(command, &args.iter().map(|s| s.as_str()).collect(), "")?;
+    if result.exit_status != 0 {
+        return Err(format!(
+            "`{}` exited with {}: {}",
+            exec_command.join(" "),
+            result.exit_status,
+            result.stderr.trim()
+        )
+        .into());
+    }
+    Ok(RetrievedXml {
+        xml: encoding::decode(&result.stdout, None),
+        size: result.stdout.len(),
+        url: url.to_string(),
+        redirect_list: Vec::new(),
+        status: None,
+        update_hints: UpdateHints::default(),
+    })
+}
+
+//Retrieves the feed advertised by an HTML page (i.e. when `url` in the config is that of a blog's top page).
+//When the page advertises several feeds, the first one which can be parsed is used.
+//The result is stored in the database so that the page is not fetched on every run (see `retrieve_cached_discovered_xml()`).
+pub fn retrieve_discovered_xml(
+    db: &Database,
+    feed_config: &FeedConfig,
+    retrieved_html: &RetrievedXml,
+) -> Result<RetrievedXml, Box<dyn Error>> {
+    let candidate_list: Vec<FeedCandidate> =
+        discovery::discover(&retrieved_html.xml, &retrieved_html.url);
+    if candidate_list.is_empty() {
+        return Err(format!("No feed is found in {}", retrieved_html.url).into());
+    }
+    let mut last_error: Box<dyn Error> = "".into();
+    for candidate in &candidate_list {
+        match retrieve_xml_from(candidate.get_url(), &feed_config.http_options) {
+            Ok(v) if !matches!(FeedType::new(&v.xml), FeedType::Unknown) => {
+                info!(
+                    "Discovered {} feed(s) in {}; using {} (set it as `url` to skip the discovery)",
+                    candidate_list.len(),
+                    feed_config.get_url(),
+                    candidate.get_url()
+                );
+                db.upsert_discovery(feed_config.get_url(), candidate.get_url());
+                return Ok(v);
+            }
+            Ok(_) => {
+                warn!(
+                    "Skipped {} discovered in {} as it is not an RSS 2.0 or Atom feed",
+                    candidate.get_url(),
+                    feed_config.get_url()
+                );
+                last_error =
+                    format!("{} is not an RSS 2.0 or Atom feed", candidate.get_url()).into();
+            }
+            Err(e) => {
+                warn!(
+                    "Failed to fetch {} discovered in {}: {}",
+                    candidate.get_url(),
+                    feed_config.get_url(),
+                    e
+                );
+                last_error = e;
+            }
+        }
+    }
+    Err(last_error)
+}
+
+//Retrieves the feed discovered in a previous run, without fetching the HTML page.
+//Returns `None` when there is no such feed, the discovery is old, or the feed cannot be fetched or parsed,
+// in which case the page should be fetched and the discovery should be done again.
+pub fn retrieve_cached_discovered_xml(
+    db: &Database,
+    feed_config: &FeedConfig,
+) -> Option<RetrievedXml> {
+    let (feed_url, update_date) = db.select_discovery(feed_config.get_url())?;
+    if Utc::now() - update_date > chrono::Duration::from_std(DISCOVERY_MAX_AGE).unwrap() {
+        debug!("Discovering the feed of {} again", feed_config.get_url());
+        db.delete_discovery(feed_config.get_url());
+        return None;
+    }
+    match retrieve_xml_from(&feed_url, &feed_config.http_options) {
+        Ok(v) if !matches!(FeedType::new(&v.xml), FeedType::Unknown) => Some(v),
+        result => {
+            info!(
+                "The feed {} discovered in {} is no longer available{}; discovering it again",
+                feed_url,
+                feed_config.get_url(),
+                result
+                    .err()
+                    .map(|e| format!(" ({})", e))
+                    .unwrap_or_default()
+            );
+            db.delete_discovery(feed_config.get_url());
+            None
+        }
+    }
+}
+
+//What a feed contained, returned by `handle_feed()` and its variants.
+pub struct FeedSummary {
+    pub item_count: usize,
+    pub update_hints: UpdateHints,
+}
+
+impl From<&Rss> for FeedSummary {
+    fn from(rss: &Rss) -> Self {
+        FeedSummary {
+            item_count: rss.get_item_list().len(),
+            update_hints: rss.get_update_hints().clone(),
+        }
+    }
+}
+
+impl From<&Atom> for FeedSummary {
+    fn from(atom: &Atom) -> Self {
+        FeedSummary {
+            item_count: atom.get_entry_list().len(),
+            update_hints: atom.get_update_hints().clone(),
+        }
+    }
+}
+
+//Whether the feed should be fetched now, i.e. the time hinted by the feed or the server has passed.
+pub fn is_due(db: &Database, feed_config: &FeedConfig) -> bool {
+    if !feed_config.should_honor_update_hints {
+        return true;
+    }
+    let schedule: Schedule = db.select_schedule(feed_config.get_url());
+    if schedule.is_due(Utc::now()) {
+        return true;
+    }
+    debug!(
+        "Skipped {} as hinted ({:?})",
+        feed_config.get_url(),
+        schedule
+    );
+    false
+}
+
+//Records when the feed should be fetched next.
+pub fn schedule_next_fetch(db: &Database, feed_config: &FeedConfig, update_hints: &UpdateHints) {
+    if !feed_config.should_honor_update_hints {
+        return;
+    }
+    let schedule: Schedule = update_hints.get_schedule(Utc::now());
+    if !schedule.is_empty() {
+        debug!(
+            "Next fetch of {}: {:?} ({:?})",
+            feed_config.get_url(),
+            schedule,
+            update_hints
+        );
+    }
+    db.upsert_schedule(feed_config.get_url(), &schedule);
+}
+
+//A server responding with `429 Too Many Requests` or `503 Service Unavailable` may ask us to come back later via `Retry-After`.
+pub fn schedule_retry(db: &Database, feed_config: &FeedConfig, error: &(dyn Error + 'static)) {
+    if let Some(StatusError {
+        retry_after: Some(retry_after),
+        ..
+    }) = error.downcast_ref::<StatusError>()
+    {
+        //The skipped hours and days are kept until the feed is fetched successfully.
+        let schedule: Schedule = db.select_schedule(feed_config.get_url());
+        let update_hints = UpdateHints {
+            retry_after: Some(*retry_after),
+            skip_hour_list: schedule.skip_hour_list,
+            skip_day_list: schedule.skip_day_list,
+            ..Default::default()
+        };
+        schedule_next_fetch(db, feed_config, &update_hints);
+    }
+}
+
+//Records the redirects of a fetch, and reports (or follows) a permanent move of the feed.
+pub fn handle_redirects(
+    db: &Database,
+    config: &Config,
+    feed_config: &FeedConfig,
+    retrieved_xml: &RetrievedXml,
+) {
+    let url: &str = feed_config.get_url();
+    let permanent_url: Option<String> = http::get_permanent_url(&retrieved_xml.redirect_list);
+    db.upsert_redirects(
+        url,
+        &retrieved_xml.url,
+        &permanent_url,
+        &retrieved_xml.redirect_list,
+    );
+
+    let permanent_url: String = match permanent_url {
+        Some(u) => u,
+        None => return,
+    };
+    if !feed_config.should_update_url_on_permanent_redirect {
+        warn!(
+            "Feed moved permanently: {} -> {} (update `url` in the config)",
+            url, permanent_url
+        );
+        return;
+    }
+    match config.replace_feed_url(url, &permanent_url) {
+        Ok(()) => {
+            info!("Feed URL updated: {} -> {}", url, permanent_url);
+            db.upsert_feed_id(&permanent_url, &get_feed_id(db, feed_config));
+            db.delete_redirects(url);
+        }
+        Err(e) => {
+            error!("Failed to update the feed URL {}: {}", url, e);
+        }
+    }
+}
+
+//Returns the identifier of the feed, which is the first `url` of the feed.
+//It is inherited when `url` is updated to the URL the feed moved to, whether automatically (`should_update_url_on_permanent_redirect`)
+// or manually (e.g. following `rsst redirects`), so that the items identified by `<guid>` or `<link>` do not look new.
+pub fn get_feed_id(db: &Database, feed_config: &FeedConfig) -> String {
+    let url: &str = feed_config.get_url();
+    if let Some(id) = db.select_feed_id(url) {
+        return id;
+    }
+    let id: String = db
+        .select_urls_moved_to(url)
+        .iter()
+        .find_map(|e| db.select_feed_id(e))
+        .unwrap_or_else(|| url.to_string());
+    db.upsert_feed_id(url, &id);
+    id
+}
+
+//Sorts the items oldest-first so that triggers are pulled in chronological order.
+//When some of the items lack dates, we keep the document order as dated and undated items cannot be compared.
+fn sort_chronologically<T>(item_list: &mut [T], get_date: impl Fn(&T) -> Option<DateTime<Utc>>) {
+    if item_list.iter().all(|e| get_date(e).is_some()) {
+        item_list.sort_by_key(|e| get_date(e));
+    }
+}
+
+//Returns the newest item, or the first one (i.e. the newest one for most feeds) when some of the items lack dates.
+fn find_latest<T>(item_list: &[T], get_date: impl Fn(&T) -> Option<DateTime<Utc>>) -> Option<&T> {
+    if item_list.iter().all(|e| get_date(e).is_some()) {
+        item_list.iter().rev().max_by_key(|e| get_date(e))
+    } else {
+        item_list.first()
+    }
+}
+
+//Returns `true` when the item is older than `max_entry_age`.
+//Items without dates are never regarded as stale.
+fn is_stale(date: &Option<DateTime<Utc>>, feed_config: &FeedConfig) -> bool {
+    match (date, feed_config.max_entry_age) {
+        (Some(date), Some(max_entry_age)) => match chrono::Duration::from_std(max_entry_age) {
+            Ok(max_entry_age) => *date < Utc::now() - max_entry_age,
+            Err(_) => false,
+        },
+        _ => false,
+    }
+}
+
+//Logs the message as a warning and sends it via the alert triggers.
+fn raise_alert(config: &Config, title: &str, link: &str, description: &str) {
+    warn!("{}: {}", title, description);
+
+    let trigger_info = TriggerInfo::new(
+        &Some(title.to_string()),
+        &Some(link.to_string()),
+        &Some(description.to_string()),
+        &Some(Utc::now().to_rfc2822()),
+    );
+    for alert_trigger in config.get_alert_trigger_list() {
+        if alert_trigger.pull_trigger(&trigger_info).is_err() {
+            error!("Failed to send an alert: {}", title);
+        }
+    }
+}
+
+//Returns `true` when the number of new items exceeds `flood_threshold`.
+//In that case, the caller should record the items without pulling triggers for each of them
+// since it is more likely that the feed supplier has changed the link format than that so many blog entries are posted at once.
+fn handle_flood(
+    config: &Config,
+    feed_config: &FeedConfig,
+    feed_title: &str,
+    feed_link: &str,
+    new_item_title_list: &[String],
+) -> bool {
+    let num_new_items = new_item_title_list.len();
+    match feed_config.flood_threshold {
+        Some(flood_threshold) if num_new_items > flood_threshold => (),
+        _ => return false,
+    }
+
+    raise_alert(
+        config,
+        "rsst: Too many new entries",
+        feed_config.get_url(),
+        &format!(
+            "{} new entries were found in {} (threshold: {}). They are recorded without pulling triggers.",
+            num_new_items,
+            feed_config.get_url(),
+            feed_config.flood_threshold.unwrap(),
+        ),
+    );
+
+    if feed_config.should_send_flood_summary {
+        let trigger_info = TriggerInfo::new(
+            &Some(format!("{} new entries in {}", num_new_items, feed_title)),
+            &Some(feed_link.to_string()),
+            &Some(new_item_title_list.join("\n")),
+            &None,
+        );
+        for trigger in config.get_trigger_list() {
+            if trigger.pull_trigger(&trigger_info).is_err() {
+                error!(
+                    "Failed to send a flood summary of {}",
+                    feed_config.get_url()
+                );
+            }
+        }
+    }
+
+    true
+}
+
+pub fn handle_feed(
     db: &Database,
     contents: &str,
-    trigger_list: &[Box<dyn Trigger>],
+    document_url: &str,
+    config: &Config,
+    feed_config: &FeedConfig,
+) -> Result<FeedSummary, Box<dyn Error>> {
+    match FeedType::new(contents) {
+        FeedType::Rss => Ok(handle_rss_feed_case(
+            db,
+            contents,
+            document_url,
+            config,
+            feed_config,
+        )),
+        FeedType::Atom => Ok(handle_atom_feed_case(
+            db,
+            contents,
+            document_url,
+            config,
+            feed_config,
+        )),
+        FeedType::Unknown => Err(format!("{} is not an RSS 2.0 or Atom feed", document_url).into()),
+    }
+}
+
+//Subscribes to the WebSub hub of the feed if any.
+fn handle_websub(
+    db: &Database,
+    config: &Config,
     feed_config: &FeedConfig,
+    hub_url: &Option<String>,
+    self_url: &Option<String>,
 ) {
-    let rss = Rss::new(contents, feed_config);
+    if let (Some(websub_config), Some(hub_url)) = (config.get_websub_config(), hub_url) {
+        if feed_config.should_use_websub {
+            //The topic is the self link as the hub only knows the feed by it.
+            let topic_url: &str = self_url.as_deref().unwrap_or(feed_config.get_url());
+            websub::subscribe_if_needed(db, websub_config, feed_config, hub_url, topic_url);
+        }
+    }
+}
+
+pub fn handle_rss_feed_case(
+    db: &Database,
+    contents: &str,
+    document_url: &str,
+    config: &Config,
+    feed_config: &FeedConfig,
+) -> FeedSummary {
+    handle_rss(
+        db,
+        Rss::new(contents, feed_config),
+        document_url,
+        config,
+        feed_config,
+    )
+}
+
+//An HTML page scraped via `scrape_config` is handled as if it were an RSS feed.
+pub fn handle_scraped_page_case(
+    db: &Database,
+    contents: &str,
+    document_url: &str,
+    config: &Config,
+    feed_config: &FeedConfig,
+) -> FeedSummary {
+    let page: ScrapedPage = scrape::scrape(
+        contents,
+        document_url,
+        feed_config.scrape_config.as_ref().unwrap(),
+    );
+    handle_rss(
+        db,
+        Rss::from_scraped_page(page, feed_config),
+        document_url,
+        config,
+        feed_config,
+    )
+}
+
+fn handle_rss(
+    db: &Database,
+    mut rss: Rss,
+    document_url: &str,
+    config: &Config,
+    feed_config: &FeedConfig,
+) -> FeedSummary {
+    let trigger_list: &[Box<dyn Trigger>] = config.get_trigger_list();
+
+    rss.resolve_relative_urls(document_url);
+    rss.canonicalize_urls();
+    rss.set_feed_id(&get_feed_id(db, feed_config));
+    for warning in rss.get_warning_list() {
+        warn!("{}: {}", feed_config.get_url(), warning);
+    }
+    handle_websub(
+        db,
+        config,
+        feed_config,
+        rss.get_hub_url(),
+        rss.get_self_url(),
+    );
 
     let parent_hash = rss.hash_code();
 
@@ -61,19 +582,14 @@ pub fn handle_rss_feed_case(
             rss.get_link()
         );
 
-        let latest_feed_item = rss.get_item_list().first().unwrap();
+        let latest_feed_item =
+            find_latest(rss.get_item_list(), |e| *e.get_parsed_pub_date()).unwrap();
 
         //To confirm that the triggers successfully work for the new site,
         // we pull each trigger only for the latest feed item.
-        let is_trigger_success = trigger_list.iter().all(|e| {
-            e.pull_trigger(&TriggerInfo::new(
-                latest_feed_item.get_title(),
-                latest_feed_item.get_link(),
-                latest_feed_item.get_description(),
-                latest_feed_item.get_pub_date(),
-            ))
-            .is_ok()
-        });
+        let is_trigger_success = trigger_list
+            .iter()
+            .all(|e| e.pull_trigger(&TriggerInfo::from(latest_feed_item)).is_ok());
 
         if is_trigger_success {
             db.insert_rss_feed(&rss);
@@ -91,34 +607,73 @@ pub fn handle_rss_feed_case(
             db.select_feed_items(&rss.get_item_list().iter().map(|e| e.hash_code()).collect());
         let mut new_rss_items: Vec<RssItem> = rss.get_item_list().clone();
         new_rss_items.retain(|e| !existent_rss_items.contains(&e.hash_code()));
+        sort_chronologically(&mut new_rss_items, |e| *e.get_parsed_pub_date());
 
         debug!("New rss items: {:?}", new_rss_items);
 
+        //Stale items are recorded without pulling triggers.
+        let (stale_rss_items, new_rss_items): (Vec<RssItem>, Vec<RssItem>) = new_rss_items
+            .into_iter()
+            .partition(|e| is_stale(e.get_parsed_pub_date(), feed_config));
+        if !stale_rss_items.is_empty() {
+            info!(
+                "Suppressed {} stale item(s) of {}",
+                stale_rss_items.len(),
+                rss.get_link()
+            );
+            db.insert_rss_feed_items(&parent_hash, &stale_rss_items);
+        }
+
+        if handle_flood(
+            config,
+            feed_config,
+            rss.get_title(),
+            rss.get_link(),
+            &new_rss_items
+                .iter()
+                .map(|e| e.get_title().clone().unwrap_or_default())
+                .collect::<Vec<String>>(),
+        ) {
+            db.insert_rss_feed_items(&parent_hash, &new_rss_items);
+            return FeedSummary::from(&rss);
+        }
+
         for new_rss_item in new_rss_items {
-            let is_trigger_success = trigger_list.iter().all(|e| {
-                e.pull_trigger(&TriggerInfo::new(
-                    new_rss_item.get_title(),
-                    new_rss_item.get_link(),
-                    new_rss_item.get_description(),
-                    new_rss_item.get_pub_date(),
-                ))
-                .is_ok()
-            });
+            let is_trigger_success = trigger_list
+                .iter()
+                .all(|e| e.pull_trigger(&TriggerInfo::from(&new_rss_item)).is_ok());
 
             if is_trigger_success {
                 db.insert_rss_feed_items(&parent_hash, &vec![new_rss_item]);
             }
         }
     }
+
+    FeedSummary::from(&rss)
 }
 
 pub fn handle_atom_feed_case(
     db: &Database,
     contents: &str,
-    trigger_list: &[Box<dyn Trigger>],
+    document_url: &str,
+    config: &Config,
     feed_config: &FeedConfig,
-) {
-    let atom = Atom::new(contents, feed_config);
+) -> FeedSummary {
+    let trigger_list: &[Box<dyn Trigger>] = config.get_trigger_list();
+
+    let mut atom = Atom::new(contents, feed_config);
+    atom.resolve_relative_urls(document_url);
+    atom.canonicalize_urls();
+    for warning in atom.get_warning_list() {
+        warn!("{}: {}", feed_config.get_url(), warning);
+    }
+    handle_websub(
+        db,
+        config,
+        feed_config,
+        atom.get_hub_url(),
+        atom.get_self_url(),
+    );
 
     let parent_hash = atom.hash_code();
 
@@ -130,18 +685,14 @@ pub fn handle_atom_feed_case(
             atom.get_id()
         );
 
-        let latest_atom_entry = atom.get_entry_list().first().unwrap();
+        let latest_atom_entry =
+            find_latest(atom.get_entry_list(), |e| *e.get_parsed_date()).unwrap();
 
         //To confirm that the triggers successfully work for the new site,
         // we pull each trigger only for the latest feed item.
         let is_trigger_success = trigger_list.iter().all(|e| {
-            e.pull_trigger(&TriggerInfo::new(
-                &Some(latest_atom_entry.get_title().to_string()),
-                &Some(latest_atom_entry.get_id().to_string()),
-                latest_atom_entry.get_summary_or_content(),
-                &Some(latest_atom_entry.get_updated().to_string()),
-            ))
-            .is_ok()
+            e.pull_trigger(&TriggerInfo::from(latest_atom_entry))
+                .is_ok()
         });
 
         if is_trigger_success {
@@ -165,23 +716,48 @@ pub fn handle_atom_feed_case(
         );
         let mut new_atom_entries: Vec<AtomEntry> = atom.get_entry_list().clone();
         new_atom_entries.retain(|e| !existent_atom_entries.contains(&e.hash_code()));
+        sort_chronologically(&mut new_atom_entries, |e| *e.get_parsed_date());
 
         debug!("New atom entries: {:?}", new_atom_entries);
 
+        //Stale entries are recorded without pulling triggers.
+        let (stale_atom_entries, new_atom_entries): (Vec<AtomEntry>, Vec<AtomEntry>) =
+            new_atom_entries
+                .into_iter()
+                .partition(|e| is_stale(e.get_parsed_date(), feed_config));
+        if !stale_atom_entries.is_empty() {
+            info!(
+                "Suppressed {} stale entry(ies) of {}",
+                stale_atom_entries.len(),
+                atom.get_id()
+            );
+            db.insert_atom_feed_entries(&parent_hash, &stale_atom_entries);
+        }
+
+        if handle_flood(
+            config,
+            feed_config,
+            atom.get_title(),
+            atom.get_link().as_deref().unwrap_or(atom.get_id()),
+            &new_atom_entries
+                .iter()
+                .map(|e| e.get_title().to_string())
+                .collect::<Vec<String>>(),
+        ) {
+            db.insert_atom_feed_entries(&parent_hash, &new_atom_entries);
+            return FeedSummary::from(&atom);
+        }
+
         for new_atom_entry in new_atom_entries {
-            let is_trigger_success = trigger_list.iter().all(|e| {
-                e.pull_trigger(&TriggerInfo::new(
-                    &Some(new_atom_entry.get_title().to_string()),
-                    &Some(new_atom_entry.get_id().to_string()),
-                    new_atom_entry.get_summary_or_content(),
-                    &Some(new_atom_entry.get_updated().to_string()),
-                ))
-                .is_ok()
-            });
+            let is_trigger_success = trigger_list
+                .iter()
+                .all(|e| e.pull_trigger(&TriggerInfo::from(&new_atom_entry)).is_ok());
 
             if is_trigger_success {
                 db.insert_atom_feed_entries(&parent_hash, &vec![new_atom_entry]);
             }
         }
     }
+
+    FeedSummary::from(&atom)
 }
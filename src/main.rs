@@ -1,36 +1,270 @@
+use std::env;
+use std::error::Error;
+use std::panic::{self, AssertUnwindSafe};
+use std::process;
+use std::time::{Duration, Instant};
+
+use chrono::Utc;
 use log::*;
 
 use rsst::config::Config;
 use rsst::database::Database;
+use rsst::discovery;
+use rsst::feedconfig::FeedConfig;
 use rsst::feedtype::FeedType;
-use rsst::trigger::Trigger;
+use rsst::health;
+use rsst::health::FetchAttempt;
+use rsst::http::StatusError;
+use rsst::websub;
+use rsst::FeedSummary;
+use rsst::RetrievedXml;
 
 const SHOULD_DROP_TABLES_FIRST: bool = false; //for debug
 
+const CONFIG_FILE: &str = "./conf/config.json";
+
 fn main() {
-    let config = Config::new("./conf/config.json");
+    let config = Config::new(CONFIG_FILE);
 
     rsst::initialize_logger(*config.get_should_log_debug());
 
     let db = Database::new(config.get_database_file(), SHOULD_DROP_TABLES_FIRST);
 
-    let trigger_list: &Vec<Box<dyn Trigger>> = config.get_trigger_list();
+    let args: Vec<String> = env::args().collect();
+    match args.get(1).map(|s| s.as_str()) {
+        None => (),
+        Some("redirects") => {
+            print_redirects(&db);
+            return;
+        }
+        Some("health") => {
+            print_health(&db, &config);
+            return;
+        }
+        Some("websub") => {
+            websub::serve(CONFIG_FILE);
+            return;
+        }
+        Some("discover") => {
+            print_discovered_feeds(&config, args.get(2).expect("Usage: rsst discover <url>"));
+            return;
+        }
+        Some(command) => {
+            panic!("Unknown command: {}", command);
+        }
+    }
+
+    health::delete_old_fetch_attempts(&db);
 
+    //The exit status is non-zero when any feed failed, so that e.g. `cron.sh` can notify it.
+    let mut failed_url_list: Vec<&str> = Vec::new();
     for feed_config in config.get_feed_config_list() {
         debug!("URL: {:?}", feed_config);
 
-        let xml: String = rsst::retrieve_xml(feed_config.get_url());
+        if health::is_paused(&db, feed_config) {
+            debug!("Skipped {} as it is paused", feed_config.get_url());
+            continue;
+        }
+        if !rsst::is_due(&db, feed_config) {
+            continue;
+        }
 
-        match FeedType::new(&xml) {
-            FeedType::Rss => {
-                rsst::handle_rss_feed_case(&db, &xml, trigger_list, feed_config);
+        let start = Instant::now();
+        let mut fetch_attempt = FetchAttempt {
+            url: feed_config.get_url().to_string(),
+            date: Utc::now(),
+            status: None,
+            duration: Duration::ZERO,
+            error: None,
+            size: None,
+            item_count: None,
+        };
+        //A panic (e.g. a parse error in strict mode) is recorded as a failure of the feed instead of aborting the other feeds.
+        match panic::catch_unwind(AssertUnwindSafe(|| {
+            process_feed(&db, &config, feed_config, &mut fetch_attempt)
+        })) {
+            Ok(Ok(())) => (),
+            Ok(Err(e)) => {
+                error!("{}", e);
+                fetch_attempt.error = Some(e.to_string());
             }
-            FeedType::Atom => {
-                rsst::handle_atom_feed_case(&db, &xml, trigger_list, feed_config);
+            Err(payload) => {
+                let message: String = payload
+                    .downcast_ref::<String>()
+                    .cloned()
+                    .or_else(|| payload.downcast_ref::<&str>().map(|s| s.to_string()))
+                    .unwrap_or_default();
+                error!(
+                    "Panicked while processing {}: {}",
+                    feed_config.get_url(),
+                    message
+                );
+                fetch_attempt.error = Some(format!("Panicked: {}", message));
             }
-            _ => {
-                panic!("Unknown feed type.");
+        }
+        fetch_attempt.duration = start.elapsed();
+        health::record_fetch_attempt(&db, &config, feed_config, &fetch_attempt);
+        if fetch_attempt.error.is_some() {
+            failed_url_list.push(feed_config.get_url());
+        }
+    }
+
+    if !failed_url_list.is_empty() {
+        error!(
+            "{} feed(s) failed: {}",
+            failed_url_list.len(),
+            failed_url_list.join(", ")
+        );
+        process::exit(1);
+    }
+}
+
+//Fetches the feed and pulls triggers for new items.
+fn process_feed(
+    db: &Database,
+    config: &Config,
+    feed_config: &FeedConfig,
+    fetch_attempt: &mut FetchAttempt,
+) -> Result<(), Box<dyn Error>> {
+    let cached_xml: Option<RetrievedXml> = if feed_config.scrape_config.is_none() {
+        rsst::retrieve_cached_discovered_xml(db, feed_config)
+    } else {
+        None
+    };
+    let mut retrieved_xml: RetrievedXml = match cached_xml {
+        Some(v) => v,
+        None => {
+            let v: RetrievedXml = match rsst::retrieve_xml(feed_config) {
+                Ok(v) => v,
+                Err(e) => {
+                    fetch_attempt.status = e.downcast_ref::<StatusError>().map(|e| e.status);
+                    rsst::schedule_retry(db, feed_config, e.as_ref());
+                    return Err(format!("Failed to fetch {}: {}", feed_config.get_url(), e).into());
+                }
+            };
+            rsst::handle_redirects(db, config, feed_config, &v);
+            v
+        }
+    };
+    fetch_attempt.status = retrieved_xml.status;
+    fetch_attempt.size = Some(retrieved_xml.size);
+    let feed_summary: FeedSummary = if feed_config.scrape_config.is_some() {
+        rsst::handle_scraped_page_case(
+            db,
+            &retrieved_xml.xml,
+            &retrieved_xml.url,
+            config,
+            feed_config,
+        )
+    } else {
+        if let FeedType::Unknown = FeedType::new(&retrieved_xml.xml) {
+            retrieved_xml = match rsst::retrieve_discovered_xml(db, feed_config, &retrieved_xml) {
+                Ok(v) => v,
+                Err(e) => {
+                    fetch_attempt.status = e.downcast_ref::<StatusError>().map(|e| e.status);
+                    rsst::schedule_retry(db, feed_config, e.as_ref());
+                    return Err(format!(
+                        "Failed to discover a feed of {}: {}",
+                        feed_config.get_url(),
+                        e
+                    )
+                    .into());
+                }
+            };
+            fetch_attempt.status = retrieved_xml.status;
+            fetch_attempt.size = Some(retrieved_xml.size);
+        }
+        let xml: &str = &retrieved_xml.xml;
+        let document_url: &str = &retrieved_xml.url;
+
+        rsst::handle_feed(db, xml, document_url, config, feed_config)?
+    };
+    fetch_attempt.item_count = Some(feed_summary.item_count);
+    rsst::schedule_next_fetch(
+        db,
+        feed_config,
+        &feed_summary.update_hints.merge(&retrieved_xml.update_hints),
+    );
+    Ok(())
+}
+
+//Reports the health of each feed in the config.
+fn print_health(db: &Database, config: &Config) {
+    for feed_config in config.get_feed_config_list() {
+        let url: &str = feed_config.get_url();
+        let (attempt_count, failure_count, average_duration) = db.select_fetch_attempt_stats(url);
+        let stats: String = format!(
+            "{} attempt(s), {} failure(s), {:.0} ms on average",
+            attempt_count,
+            failure_count,
+            average_duration.unwrap_or_default()
+        );
+        match db.select_feed_health(url) {
+            None => println!("[UNKNOWN] {} (never fetched)", url),
+            Some(health) => {
+                let state: &str = if health.paused_date.is_some() {
+                    "PAUSED"
+                } else if health.consecutive_failure_count > 0 {
+                    "FAILING"
+                } else {
+                    "OK"
+                };
+                println!("[{}] {} ({})", state, url, stats);
+                println!(
+                    "    last success: {}",
+                    health
+                        .last_success_date
+                        .map(|d| d.to_rfc3339())
+                        .unwrap_or_else(|| "never".to_string())
+                );
+                if let Some(last_error) = &health.last_error {
+                    println!(
+                        "    {} consecutive failure(s), last error: {}",
+                        health.consecutive_failure_count, last_error
+                    );
+                }
+                if let Some(paused_date) = health.paused_date {
+                    println!("    paused at {}", paused_date.to_rfc3339());
+                }
             }
         }
     }
 }
+
+//Lists the feed URLs which have moved permanently.
+fn print_redirects(db: &Database) {
+    let redirect_list = db.select_permanent_redirects();
+    if redirect_list.is_empty() {
+        println!("No permanent redirects.");
+        return;
+    }
+    for (url, permanent_url, update_date) in redirect_list {
+        println!("{} -> {} (checked at {})", url, permanent_url, update_date);
+    }
+}
+
+//Lists the feeds advertised by an HTML page.
+//The page is fetched with the top-level `http_options` (e.g. `proxy` and `user_agent`) as it would be once registered as `url`.
+fn print_discovered_feeds(config: &Config, url: &str) {
+    let mut feed_config = FeedConfig::new(url);
+    feed_config.http_options = config.get_http_options().clone();
+    let retrieved_html: RetrievedXml = rsst::retrieve_xml(&feed_config).unwrap();
+    if !matches!(FeedType::new(&retrieved_html.xml), FeedType::Unknown) {
+        println!("{} is a feed itself.", retrieved_html.url);
+        return;
+    }
+    let candidate_list = discovery::discover(&retrieved_html.xml, &retrieved_html.url);
+    if candidate_list.is_empty() {
+        println!("No feeds found.");
+        return;
+    }
+    for (i, candidate) in candidate_list.iter().enumerate() {
+        println!(
+            "{}{} [{}] {}",
+            if i == 0 { "* " } else { "  " },
+            candidate.get_url(),
+            candidate.get_mime_type(),
+            candidate.get_title().as_deref().unwrap_or("(no title)")
+        );
+    }
+}
@@ -0,0 +1,125 @@
+use json::JsonValue;
+use quick_xml::events::BytesStart;
+
+use super::xmlutil;
+
+//Represents a media file attached to a feed item (e.g. an episode of a podcast).
+#[derive(Debug, Clone)]
+pub struct Enclosure {
+    url: String,
+    mime_type: Option<String>,
+    length: Option<u64>,
+    duration: Option<u64>,
+}
+
+impl Enclosure {
+    //`<enclosure url="..." length="..." type="...">` of RSS.
+    pub fn from_rss_enclosure(e: &BytesStart) -> Option<Self> {
+        Some(Enclosure {
+            url: xmlutil::get_attribute(e, b"url")?,
+            mime_type: xmlutil::get_attribute(e, b"type"),
+            length: xmlutil::get_attribute(e, b"length").and_then(|s| s.trim().parse().ok()),
+            duration: None,
+        })
+    }
+
+    //`<link rel="enclosure" href="..." length="..." type="...">` of Atom.
+    pub fn from_atom_link(e: &BytesStart) -> Option<Self> {
+        Some(Enclosure {
+            url: xmlutil::get_attribute(e, b"href")?,
+            mime_type: xmlutil::get_attribute(e, b"type"),
+            length: xmlutil::get_attribute(e, b"length").and_then(|s| s.trim().parse().ok()),
+            duration: None,
+        })
+    }
+
+    //`<media:content url="..." type="..." fileSize="..." duration="...">` of Media RSS.
+    pub fn from_media_content(e: &BytesStart) -> Option<Self> {
+        let mime_type: Option<String> = xmlutil::get_attribute(e, b"type").or_else(|| {
+            //`medium` is a coarse version of `type`.
+            xmlutil::get_attribute(e, b"medium").map(|medium| format!("{}/*", medium))
+        });
+        Some(Enclosure {
+            url: xmlutil::get_attribute(e, b"url")?,
+            mime_type,
+            length: xmlutil::get_attribute(e, b"fileSize").and_then(|s| s.trim().parse().ok()),
+            duration: xmlutil::get_attribute(e, b"duration").and_then(|s| parse_duration(&s)),
+        })
+    }
+
+    pub fn get_url(&self) -> &str {
+        &self.url
+    }
+
+    pub fn set_url(&mut self, url: String) {
+        self.url = url;
+    }
+
+    pub fn get_mime_type(&self) -> &Option<String> {
+        &self.mime_type
+    }
+
+    //in bytes
+    pub fn get_length(&self) -> &Option<u64> {
+        &self.length
+    }
+
+    //in seconds
+    pub fn get_duration(&self) -> &Option<u64> {
+        &self.duration
+    }
+
+    pub fn set_duration(&mut self, duration: Option<u64>) {
+        self.duration = duration;
+    }
+
+    pub fn is_image(&self) -> bool {
+        self.mime_type
+            .as_ref()
+            .is_some_and(|s| s.starts_with("image/"))
+    }
+
+    pub fn to_json(&self) -> JsonValue {
+        json::object! {
+            url: self.url.clone(),
+            mime_type: self.mime_type.clone(),
+            length: self.length,
+            duration: self.duration,
+        }
+    }
+}
+
+//URL of `<media:thumbnail url="...">` or `<itunes:image href="...">`.
+pub fn get_thumbnail_url(e: &BytesStart) -> Option<String> {
+    xmlutil::get_attribute(e, b"url").or_else(|| xmlutil::get_attribute(e, b"href"))
+}
+
+//Parses a duration of the form `SS`, `MM:SS` or `HH:MM:SS` (as used in `<itunes:duration>`) into seconds.
+//Fractional seconds (e.g. `"123.45"`) are truncated, and a value which overflows is regarded as invalid.
+pub fn parse_duration(s: &str) -> Option<u64> {
+    let mut ret: u64 = 0;
+    for field in s.trim().split(':') {
+        let field: u64 = field.split('.').next()?.parse().ok()?;
+        ret = ret.checked_mul(60)?.checked_add(field)?;
+    }
+    Some(ret)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_duration() {
+        assert_eq!(Some(59), parse_duration("59"));
+        assert_eq!(Some(123), parse_duration(" 123.45 "));
+        assert_eq!(Some(90), parse_duration("1:30"));
+        assert_eq!(Some(3723), parse_duration("01:02:03"));
+        assert_eq!(None, parse_duration(""));
+        assert_eq!(None, parse_duration("1:xx"));
+        assert_eq!(None, parse_duration("-1"));
+        assert_eq!(Some(u64::MAX), parse_duration(&u64::MAX.to_string()));
+        assert_eq!(None, parse_duration(&format!("{}:00", u64::MAX)));
+        assert_eq!(None, parse_duration(&format!("1:{}", u64::MAX)));
+    }
+}
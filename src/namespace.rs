@@ -0,0 +1,16 @@
+//Namespace URIs of the elements we are interested in.
+
+pub const ATOM: &[u8] = b"http://www.w3.org/2005/Atom";
+
+//Some old feeds still use Atom 0.3.
+pub const ATOM_0_3: &[u8] = b"http://purl.org/atom/ns#";
+
+pub const DUBLIN_CORE: &[u8] = b"http://purl.org/dc/elements/1.1/";
+
+pub const CONTENT: &[u8] = b"http://purl.org/rss/1.0/modules/content/";
+
+pub const ITUNES: &[u8] = b"http://www.itunes.com/dtds/podcast-1.0.dtd";
+
+pub const MEDIA: &[u8] = b"http://search.yahoo.com/mrss/";
+
+pub const SYNDICATION: &[u8] = b"http://purl.org/rss/1.0/modules/syndication/";
@@ -2,11 +2,23 @@ use std::collections::hash_map::DefaultHasher;
 use std::hash::Hash;
 use std::hash::Hasher;
 use std::rc::Rc;
+use std::time::Duration;
 
+use chrono::{DateTime, Utc};
 use quick_xml::events::Event;
 use quick_xml::Reader;
 
+use super::date;
 use super::feedconfig::FeedConfig;
+use super::feedconfig::IdentityStrategy;
+use super::media;
+use super::media::Enclosure;
+use super::namespace;
+use super::schedule;
+use super::schedule::UpdateHints;
+use super::scrape::ScrapedPage;
+use super::urlutil;
+use super::xmlutil;
 
 #[derive(Debug)]
 pub struct Rss {
@@ -14,6 +26,10 @@ pub struct Rss {
     link: String,
     description: String,
     item_list: Vec<RssItem>,
+    hub_url: Option<String>,
+    self_url: Option<String>,
+    update_hints: UpdateHints,
+    warning_list: Vec<String>,
     feed_config: Rc<FeedConfig>,
 }
 
@@ -23,6 +39,20 @@ pub struct RssItem {
     link: Option<String>,
     description: Option<String>,
     pub_date: Option<String>,
+    parsed_pub_date: Option<DateTime<Utc>>,
+    guid: Option<String>,
+    is_guid_permalink: bool,
+    //`link` is the permalink `<guid>` as `<link>` is missing.
+    is_link_from_guid: bool,
+    //scope of `<guid>` and `<link>` in `hash_code()`; see `set_feed_id()`
+    feed_id: String,
+    authors: Vec<String>,
+    categories: Vec<String>,
+    content: Option<String>,
+    comments: Option<String>,
+    enclosures: Vec<Enclosure>,
+    thumbnail: Option<String>,
+    duration: Option<u64>,
     feed_config: Rc<FeedConfig>,
 }
 
@@ -33,11 +63,26 @@ enum TagType {
     ChannelTitle,
     ChannelLink,
     ChannelDescription,
+    ChannelTtl,
+    ChannelSkipHours,
+    ChannelSkipHour,
+    ChannelSkipDays,
+    ChannelSkipDay,
+    ChannelUpdatePeriod,
+    ChannelUpdateFrequency,
     Item,
     ItemTitle,
     ItemLink,
     ItemDescription,
     ItemPubDate,
+    ItemGuid,
+    ItemAuthor,
+    ItemCategory,
+    ItemComments,
+    ItemContent,
+    ItemDuration,
+    MediaGroup,
+    MediaContent,
 }
 
 impl Rss {
@@ -47,110 +92,458 @@ impl Rss {
             link: String::new(),
             description: String::new(),
             item_list: Vec::new(),
+            hub_url: None,
+            self_url: None,
+            update_hints: UpdateHints::default(),
+            warning_list: Vec::new(),
             feed_config: Rc::new(feed_config.clone()),
         };
 
+        let repaired_xml: String;
+        let xml: &str = if feed_config.is_lenient_mode {
+            let (s, warning_list) = xmlutil::repair(xml);
+            repaired_xml = s;
+            ret.warning_list = warning_list;
+            &repaired_xml
+        } else {
+            xml
+        };
+
         let mut reader = Reader::from_str(xml);
-        reader.trim_text(true);
         reader.expand_empty_elements(true);
+        reader.check_end_names(!feed_config.is_lenient_mode);
 
         let mut buf: Vec<u8> = Vec::new();
+        let mut ns_buf: Vec<u8> = Vec::new();
 
         let mut tag_stack: Vec<TagType> = Vec::new();
 
+        //Qualified names of open elements.
+        let mut name_stack: Vec<Vec<u8>> = Vec::new();
+
+        //`xml:base` in effect for each open element.
+        let mut base_stack: Vec<Option<String>> = Vec::new();
+
+        //Text of each open element.
+        //A text can be split into several fragments (e.g. by comments or CDATA sections), so we concatenate them
+        // and assign the result when the element is closed.
+        let mut text_stack: Vec<String> = Vec::new();
+
+        //`<sy:updatePeriod>` and `<sy:updateFrequency>` are interpreted together after parsing.
+        let mut update_period: Option<String> = None;
+        let mut update_frequency: Option<String> = None;
+
         loop {
             buf.clear();
 
-            match reader.read_event(&mut buf) {
-                Ok(Event::Eof) => {
+            match reader.read_namespaced_event(&mut buf, &mut ns_buf) {
+                Ok((_, Event::Eof)) => {
                     break;
                 }
 
-                Ok(Event::Start(ref e)) => match e.name() {
-                    b"channel" => {
-                        tag_stack.push(TagType::Channel);
-                    }
-                    b"item" => {
-                        tag_stack.push(TagType::Item);
-                        ret.item_list.push(RssItem::new(&ret.feed_config));
-                    }
-                    b"title" => match tag_stack.last().unwrap() {
-                        TagType::Channel => {
-                            tag_stack.push(TagType::ChannelTitle);
+                Ok((ns, Event::Start(ref e))) => {
+                    name_stack.push(e.name().to_vec());
+                    text_stack.push(String::new());
+                    //`xml:base` may itself be relative to that of the parent.
+                    let parent_base: Option<String> = base_stack.last().cloned().flatten();
+                    let base: Option<String> = match xmlutil::get_attribute(e, b"xml:base") {
+                        Some(b) => Some(urlutil::resolve(parent_base.as_deref(), &b)),
+                        None => parent_base,
+                    };
+                    base_stack.push(base.clone());
+                    match (ns, e.local_name()) {
+                        (None, b"channel") => {
+                            tag_stack.push(TagType::Channel);
+                        }
+                        (None, b"item") => {
+                            tag_stack.push(TagType::Item);
+                            ret.item_list.push(RssItem::new(&ret.feed_config));
                         }
-                        TagType::Item => {
-                            tag_stack.push(TagType::ItemTitle);
+                        (None, b"title") => match tag_stack.last().unwrap() {
+                            TagType::Channel => {
+                                tag_stack.push(TagType::ChannelTitle);
+                            }
+                            TagType::Item => {
+                                tag_stack.push(TagType::ItemTitle);
+                            }
+                            _ => tag_stack.push(TagType::Other),
+                        },
+                        (None, b"link") => match tag_stack.last().unwrap() {
+                            TagType::Channel => {
+                                tag_stack.push(TagType::ChannelLink);
+                            }
+                            TagType::Item => {
+                                tag_stack.push(TagType::ItemLink);
+                            }
+                            _ => tag_stack.push(TagType::Other),
+                        },
+                        (None, b"description") => match tag_stack.last().unwrap() {
+                            TagType::Channel => {
+                                tag_stack.push(TagType::ChannelDescription);
+                            }
+                            TagType::Item => {
+                                tag_stack.push(TagType::ItemDescription);
+                            }
+                            _ => tag_stack.push(TagType::Other),
+                        },
+                        (None, b"guid") => match tag_stack.last().unwrap() {
+                            TagType::Item => {
+                                tag_stack.push(TagType::ItemGuid);
+                                //`isPermaLink` defaults to `true` per the specification.
+                                ret.item_list.last_mut().unwrap().is_guid_permalink =
+                                    xmlutil::get_attribute(e, b"isPermaLink").as_deref()
+                                        != Some("false");
+                            }
+                            _ => tag_stack.push(TagType::Other),
+                        },
+                        //`<dc:creator>` is regarded as the same as `<author>`.
+                        (None, b"author") | (Some(namespace::DUBLIN_CORE), b"creator") => {
+                            match tag_stack.last().unwrap() {
+                                TagType::Item => {
+                                    tag_stack.push(TagType::ItemAuthor);
+                                }
+                                _ => tag_stack.push(TagType::Other),
+                            }
                         }
-                        _ => tag_stack.push(TagType::Other),
-                    },
-                    b"link" => match tag_stack.last().unwrap() {
-                        TagType::Channel => {
-                            tag_stack.push(TagType::ChannelLink);
+                        (None, b"category") => match tag_stack.last().unwrap() {
+                            TagType::Item => {
+                                tag_stack.push(TagType::ItemCategory);
+                            }
+                            _ => tag_stack.push(TagType::Other),
+                        },
+                        (None, b"comments") => match tag_stack.last().unwrap() {
+                            TagType::Item => {
+                                tag_stack.push(TagType::ItemComments);
+                            }
+                            _ => tag_stack.push(TagType::Other),
+                        },
+                        (Some(namespace::CONTENT), b"encoded") => match tag_stack.last().unwrap() {
+                            TagType::Item => {
+                                tag_stack.push(TagType::ItemContent);
+                            }
+                            _ => tag_stack.push(TagType::Other),
+                        },
+                        (None, b"enclosure") => {
+                            if let TagType::Item = tag_stack.last().unwrap() {
+                                if let Some(enclosure) = Enclosure::from_rss_enclosure(e) {
+                                    let mut enclosure = enclosure;
+                                    enclosure.set_url(urlutil::resolve(
+                                        base.as_deref(),
+                                        enclosure.get_url(),
+                                    ));
+                                    ret.item_list.last_mut().unwrap().enclosures.push(enclosure);
+                                }
+                            }
+                            tag_stack.push(TagType::Other);
                         }
-                        TagType::Item => {
-                            tag_stack.push(TagType::ItemLink);
+                        (Some(namespace::ITUNES), b"duration") => match tag_stack.last().unwrap() {
+                            TagType::Item => {
+                                tag_stack.push(TagType::ItemDuration);
+                            }
+                            _ => tag_stack.push(TagType::Other),
+                        },
+                        (Some(namespace::ITUNES), b"image")
+                        | (Some(namespace::MEDIA), b"thumbnail") => {
+                            if let TagType::Item | TagType::MediaGroup | TagType::MediaContent =
+                                tag_stack.last().unwrap()
+                            {
+                                let item = ret.item_list.last_mut().unwrap();
+                                if item.thumbnail.is_none() {
+                                    item.thumbnail = media::get_thumbnail_url(e)
+                                        .map(|url| urlutil::resolve(base.as_deref(), &url));
+                                }
+                            }
+                            tag_stack.push(TagType::Other);
                         }
-                        _ => tag_stack.push(TagType::Other),
-                    },
-                    b"description" => match tag_stack.last().unwrap() {
-                        TagType::Channel => {
-                            tag_stack.push(TagType::ChannelDescription);
+                        //WebSub (https://www.w3.org/TR/websub/#discovery)
+                        (Some(namespace::ATOM), b"link") => {
+                            if let TagType::Channel = tag_stack.last().unwrap() {
+                                let href: Option<String> = xmlutil::get_attribute(e, b"href")
+                                    .map(|href| urlutil::resolve(base.as_deref(), &href));
+                                match xmlutil::get_attribute(e, b"rel").as_deref() {
+                                    Some("hub") if ret.hub_url.is_none() => ret.hub_url = href,
+                                    Some("self") if ret.self_url.is_none() => ret.self_url = href,
+                                    _ => (),
+                                }
+                            }
+                            tag_stack.push(TagType::Other);
                         }
-                        TagType::Item => {
-                            tag_stack.push(TagType::ItemDescription);
+                        (Some(namespace::MEDIA), b"group") => match tag_stack.last().unwrap() {
+                            TagType::Item => {
+                                tag_stack.push(TagType::MediaGroup);
+                            }
+                            _ => tag_stack.push(TagType::Other),
+                        },
+                        (Some(namespace::MEDIA), b"content") => match tag_stack.last().unwrap() {
+                            TagType::Item | TagType::MediaGroup => {
+                                if let Some(enclosure) = Enclosure::from_media_content(e) {
+                                    let mut enclosure = enclosure;
+                                    enclosure.set_url(urlutil::resolve(
+                                        base.as_deref(),
+                                        enclosure.get_url(),
+                                    ));
+                                    ret.item_list.last_mut().unwrap().enclosures.push(enclosure);
+                                }
+                                tag_stack.push(TagType::MediaContent);
+                            }
+                            _ => tag_stack.push(TagType::Other),
+                        },
+                        //update hints (https://www.rssboard.org/rss-specification#optionalChannelElements)
+                        (None, b"ttl") => match tag_stack.last().unwrap() {
+                            TagType::Channel => {
+                                tag_stack.push(TagType::ChannelTtl);
+                            }
+                            _ => tag_stack.push(TagType::Other),
+                        },
+                        (None, b"skipHours") => match tag_stack.last().unwrap() {
+                            TagType::Channel => {
+                                tag_stack.push(TagType::ChannelSkipHours);
+                            }
+                            _ => tag_stack.push(TagType::Other),
+                        },
+                        (None, b"hour") => match tag_stack.last().unwrap() {
+                            TagType::ChannelSkipHours => {
+                                tag_stack.push(TagType::ChannelSkipHour);
+                            }
+                            _ => tag_stack.push(TagType::Other),
+                        },
+                        (None, b"skipDays") => match tag_stack.last().unwrap() {
+                            TagType::Channel => {
+                                tag_stack.push(TagType::ChannelSkipDays);
+                            }
+                            _ => tag_stack.push(TagType::Other),
+                        },
+                        (None, b"day") => match tag_stack.last().unwrap() {
+                            TagType::ChannelSkipDays => {
+                                tag_stack.push(TagType::ChannelSkipDay);
+                            }
+                            _ => tag_stack.push(TagType::Other),
+                        },
+                        (Some(namespace::SYNDICATION), b"updatePeriod") => {
+                            match tag_stack.last().unwrap() {
+                                TagType::Channel => {
+                                    tag_stack.push(TagType::ChannelUpdatePeriod);
+                                }
+                                _ => tag_stack.push(TagType::Other),
+                            }
                         }
-                        _ => tag_stack.push(TagType::Other),
-                    },
-                    b"pubDate" => {
-                        if let TagType::Item = tag_stack.last().unwrap() {
-                            tag_stack.push(TagType::ItemPubDate);
+                        (Some(namespace::SYNDICATION), b"updateFrequency") => {
+                            match tag_stack.last().unwrap() {
+                                TagType::Channel => {
+                                    tag_stack.push(TagType::ChannelUpdateFrequency);
+                                }
+                                _ => tag_stack.push(TagType::Other),
+                            }
+                        }
+                        (None, b"pubDate") => match tag_stack.last().unwrap() {
+                            TagType::Item => {
+                                tag_stack.push(TagType::ItemPubDate);
+                            }
+                            _ => tag_stack.push(TagType::Other),
+                        },
+                        _ => {
+                            tag_stack.push(TagType::Other);
                         }
                     }
-                    _ => {
-                        tag_stack.push(TagType::Other);
+                }
+
+                Ok((_, Event::Text(ref e))) => {
+                    if let Some(text) = text_stack.last_mut() {
+                        text.push_str(&e.unescape_and_decode(&reader).unwrap());
                     }
-                },
+                }
 
-                Ok(Event::End(_)) => {
-                    tag_stack.pop();
+                Ok((_, Event::CData(ref e))) => {
+                    if let Some(text) = text_stack.last_mut() {
+                        text.push_str(&e.unescape_and_decode(&reader).unwrap());
+                    }
                 }
 
-                Ok(Event::Text(ref e)) => {
-                    let text: String = e.unescape_and_decode(&reader).unwrap();
-                    match tag_stack.last().unwrap() {
-                        TagType::ChannelTitle => {
-                            ret.title = text;
-                        }
-                        TagType::ChannelLink => {
-                            ret.link = text;
-                        }
-                        TagType::ChannelDescription => {
-                            ret.description = text;
-                        }
-                        TagType::ItemTitle => {
-                            ret.item_list.last_mut().unwrap().title = Some(text);
-                        }
-                        TagType::ItemLink => {
-                            ret.item_list.last_mut().unwrap().link = Some(text);
+                Ok((_, Event::End(ref e))) => {
+                    //In lenient mode, end tags may mismatch.
+                    //Elements left open (e.g. `<br>` in an unescaped HTML) are implicitly closed, and stray end tags are ignored.
+                    let depth: usize =
+                        match name_stack.iter().rposition(|n| n.as_slice() == e.name()) {
+                            Some(depth) => depth,
+                            None => continue,
+                        };
+                    while tag_stack.len() > depth {
+                        name_stack.pop();
+                        let base: Option<String> = base_stack.pop().unwrap();
+                        let tag = tag_stack.pop().unwrap();
+                        let text: String = text_stack.pop().unwrap().trim().to_string();
+
+                        //Texts of unknown elements (e.g. unescaped HTML tags in a description) are a part of the parent's text.
+                        if let TagType::Other = tag {
+                            if let Some(parent_text) = text_stack.last_mut() {
+                                parent_text.push_str(&text);
+                            }
+                            continue;
                         }
-                        TagType::ItemDescription => {
-                            ret.item_list.last_mut().unwrap().description = Some(text);
+
+                        if text.is_empty() {
+                            continue;
                         }
-                        TagType::ItemPubDate => {
-                            ret.item_list.last_mut().unwrap().pub_date = Some(text);
+
+                        match tag {
+                            TagType::ChannelTitle => {
+                                ret.title = text;
+                            }
+                            TagType::ChannelLink => {
+                                ret.link = text;
+                            }
+                            TagType::ChannelDescription => {
+                                ret.description = text;
+                            }
+                            //in minutes
+                            TagType::ChannelTtl => match text.parse::<u64>() {
+                                Ok(ttl) => {
+                                    ret.update_hints.ttl =
+                                        Some(Duration::from_secs(ttl.saturating_mul(60)));
+                                }
+                                Err(_) => {
+                                    ret.warning_list.push(format!("Invalid `<ttl>`: {}", text))
+                                }
+                            },
+                            //in GMT
+                            TagType::ChannelSkipHour => match text.parse::<u32>() {
+                                Ok(hour) if hour < 24 => {
+                                    ret.update_hints.skip_hour_list.push(hour);
+                                }
+                                //Some feeds use `24` for midnight.
+                                Ok(24) => ret.update_hints.skip_hour_list.push(0),
+                                _ => ret
+                                    .warning_list
+                                    .push(format!("Invalid `<hour>` in `<skipHours>`: {}", text)),
+                            },
+                            TagType::ChannelSkipDay => match schedule::parse_weekday(&text) {
+                                Some(day) => ret.update_hints.skip_day_list.push(day),
+                                None => ret
+                                    .warning_list
+                                    .push(format!("Invalid `<day>` in `<skipDays>`: {}", text)),
+                            },
+                            TagType::ChannelUpdatePeriod => {
+                                update_period = Some(text);
+                            }
+                            TagType::ChannelUpdateFrequency => {
+                                update_frequency = Some(text);
+                            }
+                            TagType::ItemTitle => {
+                                ret.item_list.last_mut().unwrap().title = Some(text);
+                            }
+                            TagType::ItemLink => {
+                                ret.item_list.last_mut().unwrap().link =
+                                    Some(urlutil::resolve(base.as_deref(), &text));
+                            }
+                            TagType::ItemDescription => {
+                                ret.item_list.last_mut().unwrap().description = Some(text);
+                            }
+                            TagType::ItemPubDate => {
+                                ret.item_list.last_mut().unwrap().pub_date = Some(text);
+                            }
+                            TagType::ItemGuid => {
+                                ret.item_list.last_mut().unwrap().guid = Some(text);
+                            }
+                            TagType::ItemAuthor => {
+                                let authors = &mut ret.item_list.last_mut().unwrap().authors;
+                                if !authors.contains(&text) {
+                                    authors.push(text);
+                                }
+                            }
+                            TagType::ItemCategory => {
+                                ret.item_list.last_mut().unwrap().categories.push(text);
+                            }
+                            TagType::ItemComments => {
+                                ret.item_list.last_mut().unwrap().comments =
+                                    Some(urlutil::resolve(base.as_deref(), &text));
+                            }
+                            TagType::ItemContent => {
+                                ret.item_list.last_mut().unwrap().content = Some(text);
+                            }
+                            TagType::ItemDuration => {
+                                ret.item_list.last_mut().unwrap().duration =
+                                    media::parse_duration(&text);
+                            }
+                            _ => (),
                         }
-                        _ => (),
                     }
                 }
 
                 Err(e) => {
-                    panic!("Error at position {}: {:?}", reader.buffer_position(), e)
+                    let message =
+                        format!("Error at position {}: {:?}", reader.buffer_position(), e);
+                    if !feed_config.is_lenient_mode {
+                        panic!("{}", message);
+                    }
+                    ret.warning_list.push(message);
+                    break;
                 }
 
                 _ => (),
             }
         }
 
+        ret.update_hints.update_period =
+            schedule::parse_update_period(update_period.as_deref(), update_frequency.as_deref());
+
+        //A truncated item is dropped.
+        if tag_stack.iter().any(|e| matches!(e, TagType::Item)) {
+            ret.warning_list.push(format!(
+                "Dropped an unclosed item: {:?}",
+                ret.item_list.pop().and_then(|e| e.title)
+            ));
+        }
+
+        for item in &mut ret.item_list {
+            item.parsed_pub_date = item.pub_date.as_deref().and_then(date::parse);
+            if item.link.is_none() && item.is_guid_permalink && item.guid.is_some() {
+                item.link = item.guid.clone();
+                item.is_link_from_guid = true;
+            }
+            //`<itunes:duration>` describes the (first) enclosure.
+            if let Some(enclosure) = item.enclosures.first_mut() {
+                if enclosure.get_duration().is_none() {
+                    enclosure.set_duration(item.duration);
+                }
+            }
+            if item.thumbnail.is_none() {
+                item.thumbnail = item
+                    .enclosures
+                    .iter()
+                    .find(|e| e.is_image())
+                    .map(|e| e.get_url().to_string());
+            }
+        }
+
+        ret
+    }
+
+    //Builds a feed from the entries extracted from an HTML page so that it is handled in the same way as RSS.
+    pub fn from_scraped_page(page: ScrapedPage, feed_config: &FeedConfig) -> Self {
+        let mut ret = Rss {
+            title: page.title,
+            link: page.link,
+            description: String::new(),
+            item_list: Vec::new(),
+            hub_url: None,
+            self_url: None,
+            update_hints: UpdateHints::default(),
+            warning_list: Vec::new(),
+            feed_config: Rc::new(feed_config.clone()),
+        };
+        for entry in page.entry_list {
+            let mut item = RssItem::new(&ret.feed_config);
+            item.title = entry.title;
+            item.link = entry.link;
+            item.description = entry.summary;
+            item.parsed_pub_date = entry.date.as_deref().and_then(date::parse);
+            item.pub_date = entry.date;
+            ret.item_list.push(item);
+        }
+        if ret.item_list.is_empty() {
+            ret.warning_list
+                .push("No entries matched `item_selector`.".to_string());
+        }
         ret
     }
 
@@ -162,6 +555,55 @@ impl Rss {
         hasher.finish().to_string()
     }
 
+    //Resolves relative URLs of the items which were not resolved by `xml:base`.
+    //They are resolved against the channel link, or `document_url` (i.e. the URL the feed was fetched from) when the channel link is also relative.
+    pub fn resolve_relative_urls(&mut self, document_url: &str) {
+        let base: String = urlutil::resolve(Some(document_url), &self.link);
+        let base: &str = if urlutil::is_absolute(&base) {
+            &base
+        } else {
+            document_url
+        };
+        for item in &mut self.item_list {
+            item.link = item
+                .link
+                .as_deref()
+                .map(|url| urlutil::resolve(Some(base), url));
+            item.comments = item
+                .comments
+                .as_deref()
+                .map(|url| urlutil::resolve(Some(base), url));
+            item.thumbnail = item
+                .thumbnail
+                .as_deref()
+                .map(|url| urlutil::resolve(Some(base), url));
+            for enclosure in &mut item.enclosures {
+                enclosure.set_url(urlutil::resolve(Some(base), enclosure.get_url()));
+            }
+        }
+    }
+
+    //Sets the identifier of the feed which scopes the hashes of the items (see `get_feed_id()`).
+    //This should be called before the hashes are used.
+    pub fn set_feed_id(&mut self, feed_id: &str) {
+        for item in &mut self.item_list {
+            item.feed_id = feed_id.to_string();
+        }
+    }
+
+    //Canonicalizes the links of the items according to `url_canonicalization` of the feed config.
+    //This should be called after `resolve_relative_urls()` as relative URLs cannot be canonicalized.
+    pub fn canonicalize_urls(&mut self) {
+        if let Some(canonicalization) = &self.feed_config.url_canonicalization {
+            for item in &mut self.item_list {
+                item.link = item
+                    .link
+                    .as_deref()
+                    .map(|url| urlutil::canonicalize(url, canonicalization));
+            }
+        }
+    }
+
     pub fn get_title(&self) -> &str {
         &self.title
     }
@@ -174,6 +616,26 @@ impl Rss {
         &self.description
     }
 
+    //problems found (and recovered from) in lenient mode
+    pub fn get_warning_list(&self) -> &Vec<String> {
+        &self.warning_list
+    }
+
+    //`<atom:link rel="hub">`
+    pub fn get_hub_url(&self) -> &Option<String> {
+        &self.hub_url
+    }
+
+    //`<atom:link rel="self">`
+    pub fn get_self_url(&self) -> &Option<String> {
+        &self.self_url
+    }
+
+    //`<ttl>`, `<skipHours>`, `<skipDays>` and `<sy:updatePeriod>`
+    pub fn get_update_hints(&self) -> &UpdateHints {
+        &self.update_hints
+    }
+
     pub fn get_item_list(&self) -> &Vec<RssItem> {
         &self.item_list
     }
@@ -186,17 +648,48 @@ impl RssItem {
             link: None,
             description: None,
             pub_date: None,
+            parsed_pub_date: None,
+            guid: None,
+            is_guid_permalink: true,
+            is_link_from_guid: false,
+            feed_id: String::new(),
+            authors: Vec::new(),
+            categories: Vec::new(),
+            content: None,
+            comments: None,
+            enclosures: Vec::new(),
+            thumbnail: None,
+            duration: None,
             feed_config: Rc::clone(feed_config),
         }
     }
 
     //We intentionally omit `self.description` as some feed suppliers often (e.g. everyday) update its value.
+    //`<guid>` and `<link>` are scoped to the feed as hashes are unique across all feeds in the database
+    // while guids such as `1` are not.
     pub fn hash_code(&self) -> String {
         let mut hasher = DefaultHasher::new();
-        self.title.hash(&mut hasher);
-        self.link.hash(&mut hasher);
-        if !self.feed_config.should_omit_date_field_from_hash {
-            self.pub_date.hash(&mut hasher);
+        match (&self.feed_config.identity_strategy, &self.guid, &self.link) {
+            (IdentityStrategy::Guid, Some(guid), _) => {
+                self.feed_id.hash(&mut hasher);
+                guid.hash(&mut hasher);
+            }
+            (IdentityStrategy::Link, _, Some(link)) => {
+                self.feed_id.hash(&mut hasher);
+                link.hash(&mut hasher);
+            }
+            _ => {
+                self.title.hash(&mut hasher);
+                //The link taken from `<guid>` is not hashed so that the hashes are the same as those before `<guid>` was supported.
+                if self.is_link_from_guid {
+                    None::<String>.hash(&mut hasher);
+                } else {
+                    self.link.hash(&mut hasher);
+                }
+                if !self.feed_config.should_omit_date_field_from_hash {
+                    self.pub_date.hash(&mut hasher);
+                }
+            }
         }
         hasher.finish().to_string()
     }
@@ -213,7 +706,48 @@ impl RssItem {
         &self.description
     }
 
+    pub fn get_guid(&self) -> &Option<String> {
+        &self.guid
+    }
+
+    pub fn is_guid_permalink(&self) -> bool {
+        self.is_guid_permalink
+    }
+
+    pub fn get_authors(&self) -> &Vec<String> {
+        &self.authors
+    }
+
+    pub fn get_categories(&self) -> &Vec<String> {
+        &self.categories
+    }
+
+    //`<content:encoded>`, which is usually an HTML.
+    pub fn get_content(&self) -> &Option<String> {
+        &self.content
+    }
+
+    pub fn get_comments(&self) -> &Option<String> {
+        &self.comments
+    }
+
+    pub fn get_enclosures(&self) -> &Vec<Enclosure> {
+        &self.enclosures
+    }
+
+    pub fn get_thumbnail(&self) -> &Option<String> {
+        &self.thumbnail
+    }
+
+    pub fn get_feed_config(&self) -> &FeedConfig {
+        &self.feed_config
+    }
+
     pub fn get_pub_date(&self) -> &Option<String> {
         &self.pub_date
     }
+
+    pub fn get_parsed_pub_date(&self) -> &Option<DateTime<Utc>> {
+        &self.parsed_pub_date
+    }
 }
@@ -0,0 +1,316 @@
+use std::time::Duration;
+
+use chrono::{DateTime, Datelike, Timelike, Utc, Weekday};
+
+//Hints are capped so that a wrong hint (e.g. `<ttl>` in seconds rather than minutes) does not silence a feed for long.
+//`<skipHours>` and `<skipDays>` are not subject to this.
+pub const MAX_HINTED_INTERVAL: Duration = Duration::from_secs(24 * 60 * 60);
+
+//Hints on how often a feed should be fetched, declared by the feed itself or by HTTP headers.
+#[derive(Debug, Clone, Default)]
+pub struct UpdateHints {
+    //RSS `<ttl>`
+    pub ttl: Option<Duration>,
+    //RSS `<skipHours>` (in GMT)
+    pub skip_hour_list: Vec<u32>,
+    //RSS `<skipDays>`
+    pub skip_day_list: Vec<Weekday>,
+    //`<sy:updatePeriod>` divided by `<sy:updateFrequency>`
+    pub update_period: Option<Duration>,
+    //`Cache-Control: max-age`
+    pub max_age: Option<Duration>,
+    //`Retry-After`
+    pub retry_after: Option<DateTime<Utc>>,
+}
+
+impl UpdateHints {
+    //Combines the hints of a feed with those of the HTTP response.
+    pub fn merge(mut self, other: &UpdateHints) -> Self {
+        self.ttl = self.ttl.or(other.ttl);
+        if self.skip_hour_list.is_empty() {
+            self.skip_hour_list = other.skip_hour_list.clone();
+        }
+        if self.skip_day_list.is_empty() {
+            self.skip_day_list = other.skip_day_list.clone();
+        }
+        self.update_period = self.update_period.or(other.update_period);
+        self.max_age = self.max_age.or(other.max_age);
+        self.retry_after = self.retry_after.or(other.retry_after);
+        self
+    }
+
+    //Returns when the feed should be fetched next, or `None` when there are no hints (i.e. it should be fetched every time).
+    //The longest interval wins as each hint is a request not to poll more often.
+    //With `<skipHours>` or `<skipDays>`, this is the first hour which is not skipped (which may be `now` itself).
+    pub fn get_next_fetch_date(&self, now: DateTime<Utc>) -> Option<DateTime<Utc>> {
+        let interval: Option<Duration> = [self.ttl, self.update_period, self.max_age]
+            .into_iter()
+            .flatten()
+            .max()
+            .map(|d| d.min(MAX_HINTED_INTERVAL));
+        let mut ret: Option<DateTime<Utc>> =
+            interval.map(|d| now + chrono::Duration::from_std(d).unwrap());
+        if let Some(retry_after) = self.retry_after {
+            let retry_after: DateTime<Utc> =
+                retry_after.min(now + chrono::Duration::from_std(MAX_HINTED_INTERVAL).unwrap());
+            ret = Some(ret.map_or(retry_after, |d| d.max(retry_after)));
+        }
+
+        if self.skip_hour_list.is_empty() && self.skip_day_list.is_empty() {
+            return ret;
+        }
+
+        //Moves forward to the first hour which is not skipped.
+        let mut date: DateTime<Utc> = ret.unwrap_or(now);
+        for _ in 0..(7 * 24) {
+            if !is_skipped(&self.skip_hour_list, &self.skip_day_list, date) {
+                break;
+            }
+            date = (date + chrono::Duration::hours(1))
+                .with_minute(0)
+                .unwrap()
+                .with_second(0)
+                .unwrap()
+                .with_nanosecond(0)
+                .unwrap();
+        }
+        Some(date)
+    }
+
+    pub fn get_schedule(&self, now: DateTime<Utc>) -> Schedule {
+        Schedule {
+            next_fetch_date: self.get_next_fetch_date(now),
+            skip_hour_list: self.skip_hour_list.clone(),
+            skip_day_list: self.skip_day_list.clone(),
+        }
+    }
+}
+
+//When a feed should be fetched, recorded in the database.
+//The skipped hours and days are kept as well, since the feed is fetched several times between two hours which are skipped.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Schedule {
+    pub next_fetch_date: Option<DateTime<Utc>>,
+    pub skip_hour_list: Vec<u32>,
+    pub skip_day_list: Vec<Weekday>,
+}
+
+impl Schedule {
+    pub fn is_empty(&self) -> bool {
+        self.next_fetch_date.is_none()
+            && self.skip_hour_list.is_empty()
+            && self.skip_day_list.is_empty()
+    }
+
+    pub fn is_due(&self, now: DateTime<Utc>) -> bool {
+        self.next_fetch_date.is_none_or(|d| d <= now)
+            && !is_skipped(&self.skip_hour_list, &self.skip_day_list, now)
+    }
+}
+
+//`<skipHours>` is in GMT, and so is `<skipDays>` in practice.
+fn is_skipped(skip_hour_list: &[u32], skip_day_list: &[Weekday], date: DateTime<Utc>) -> bool {
+    skip_hour_list.contains(&date.hour()) || skip_day_list.contains(&date.weekday())
+}
+
+//`<sy:updatePeriod>` and `<sy:updateFrequency>` of the Syndication module.
+//The period defaults to `daily` and the frequency defaults to `1`.
+pub fn parse_update_period(period: Option<&str>, frequency: Option<&str>) -> Option<Duration> {
+    if period.is_none() && frequency.is_none() {
+        return None;
+    }
+    let period: u64 = match period.unwrap_or("daily").trim() {
+        "hourly" => 60 * 60,
+        "daily" => 24 * 60 * 60,
+        "weekly" => 7 * 24 * 60 * 60,
+        "monthly" => 30 * 24 * 60 * 60,
+        "yearly" => 365 * 24 * 60 * 60,
+        _ => return None,
+    };
+    let frequency: u64 = frequency
+        .and_then(|s| s.trim().parse().ok())
+        .filter(|f| *f > 0)
+        .unwrap_or(1);
+    Some(Duration::from_secs(period / frequency))
+}
+
+//`<day>` of `<skipDays>`
+pub fn parse_weekday(s: &str) -> Option<Weekday> {
+    s.trim().parse().ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    //a Monday
+    fn date(s: &str) -> DateTime<Utc> {
+        format!("2026-10-19T{}Z", s).parse().unwrap()
+    }
+
+    #[test]
+    fn test_no_hints() {
+        let hints = UpdateHints::default();
+        assert_eq!(None, hints.get_next_fetch_date(date("10:30:00")));
+        let schedule = hints.get_schedule(date("10:30:00"));
+        assert!(schedule.is_empty());
+        assert!(schedule.is_due(date("10:30:00")));
+    }
+
+    #[test]
+    fn test_interval_only() {
+        let hints = UpdateHints {
+            ttl: Some(Duration::from_secs(30 * 60)),
+            update_period: Some(Duration::from_secs(60 * 60)),
+            max_age: Some(Duration::from_secs(60)),
+            ..Default::default()
+        };
+        //The longest one wins.
+        assert_eq!(
+            Some(date("11:30:00")),
+            hints.get_next_fetch_date(date("10:30:00"))
+        );
+
+        let schedule = hints.get_schedule(date("10:30:00"));
+        assert!(!schedule.is_due(date("11:00:00")));
+        assert!(schedule.is_due(date("11:30:00")));
+
+        //capped
+        let hints = UpdateHints {
+            ttl: Some(Duration::from_secs(7 * 24 * 60 * 60)),
+            ..Default::default()
+        };
+        assert_eq!(
+            Some(date("10:30:00") + chrono::Duration::hours(24)),
+            hints.get_next_fetch_date(date("10:30:00"))
+        );
+    }
+
+    #[test]
+    fn test_skip_only() {
+        let hints = UpdateHints {
+            skip_hour_list: vec![11, 12],
+            ..Default::default()
+        };
+
+        //The current hour is not skipped.
+        let schedule = hints.get_schedule(date("10:30:00"));
+        assert_eq!(Some(date("10:30:00")), schedule.next_fetch_date);
+        assert!(schedule.is_due(date("10:45:00")));
+        //Later runs in the skipped hours are not due even though the recorded date has passed.
+        assert!(!schedule.is_due(date("11:00:00")));
+        assert!(!schedule.is_due(date("12:59:59")));
+        assert!(schedule.is_due(date("13:00:00")));
+
+        //The current hour is skipped.
+        let schedule = hints.get_schedule(date("11:30:00"));
+        assert_eq!(Some(date("13:00:00")), schedule.next_fetch_date);
+        assert!(!schedule.is_due(date("12:30:00")));
+        assert!(schedule.is_due(date("13:00:00")));
+
+        let hints = UpdateHints {
+            skip_day_list: vec![Weekday::Mon, Weekday::Tue],
+            ..Default::default()
+        };
+        assert_eq!(
+            Some("2026-10-21T00:00:00Z".parse().unwrap()),
+            hints.get_next_fetch_date(date("10:30:00"))
+        );
+    }
+
+    #[test]
+    fn test_interval_and_skip() {
+        let hints = UpdateHints {
+            ttl: Some(Duration::from_secs(60 * 60)),
+            skip_hour_list: vec![11, 12],
+            ..Default::default()
+        };
+        //The interval ends in a skipped hour.
+        assert_eq!(
+            Some(date("13:00:00")),
+            hints.get_next_fetch_date(date("10:30:00"))
+        );
+        //The interval ends in an hour which is not skipped.
+        assert_eq!(
+            Some(date("14:30:00")),
+            hints.get_next_fetch_date(date("13:30:00"))
+        );
+
+        let schedule = hints.get_schedule(date("08:30:00"));
+        assert_eq!(Some(date("09:30:00")), schedule.next_fetch_date);
+        assert!(!schedule.is_due(date("09:00:00")));
+        assert!(schedule.is_due(date("10:00:00")));
+        assert!(!schedule.is_due(date("11:00:00")));
+    }
+
+    #[test]
+    fn test_retry_after() {
+        let hints = UpdateHints {
+            ttl: Some(Duration::from_secs(60 * 60)),
+            retry_after: Some(date("12:00:00")),
+            ..Default::default()
+        };
+        assert_eq!(
+            Some(date("12:00:00")),
+            hints.get_next_fetch_date(date("10:30:00"))
+        );
+
+        //shorter than the interval
+        let hints = UpdateHints {
+            ttl: Some(Duration::from_secs(60 * 60)),
+            retry_after: Some(date("11:00:00")),
+            ..Default::default()
+        };
+        assert_eq!(
+            Some(date("11:30:00")),
+            hints.get_next_fetch_date(date("10:30:00"))
+        );
+
+        //capped
+        let hints = UpdateHints {
+            retry_after: Some(date("10:30:00") + chrono::Duration::days(30)),
+            ..Default::default()
+        };
+        assert_eq!(
+            Some(date("10:30:00") + chrono::Duration::hours(24)),
+            hints.get_next_fetch_date(date("10:30:00"))
+        );
+    }
+
+    #[test]
+    fn test_merge() {
+        let feed_hints = UpdateHints {
+            ttl: Some(Duration::from_secs(60)),
+            skip_hour_list: vec![1],
+            ..Default::default()
+        };
+        let http_hints = UpdateHints {
+            ttl: Some(Duration::from_secs(120)),
+            max_age: Some(Duration::from_secs(180)),
+            skip_hour_list: vec![2],
+            ..Default::default()
+        };
+        let hints = feed_hints.merge(&http_hints);
+        assert_eq!(Some(Duration::from_secs(60)), hints.ttl);
+        assert_eq!(Some(Duration::from_secs(180)), hints.max_age);
+        assert_eq!(vec![1], hints.skip_hour_list);
+    }
+
+    #[test]
+    fn test_parse_update_period() {
+        assert_eq!(None, parse_update_period(None, None));
+        assert_eq!(
+            Some(Duration::from_secs(24 * 60 * 60)),
+            parse_update_period(None, Some("1"))
+        );
+        assert_eq!(
+            Some(Duration::from_secs(30 * 60)),
+            parse_update_period(Some("hourly"), Some("2"))
+        );
+        assert_eq!(
+            Some(Duration::from_secs(7 * 24 * 60 * 60)),
+            parse_update_period(Some("weekly"), Some("0"))
+        );
+        assert_eq!(None, parse_update_period(Some("fortnightly"), None));
+    }
+}
@@ -0,0 +1,123 @@
+use scraper::{ElementRef, Html, Selector};
+
+use super::urlutil;
+
+//CSS selectors to extract entries from an HTML page without feeds (e.g. a changelog page).
+//The selectors other than `item_selector` are applied to each element matched by `item_selector`.
+#[derive(Debug, Clone)]
+pub struct ScrapeConfig {
+    pub item_selector: Selector,
+    //When `None`, the text of the link element (or the item itself) is used.
+    pub title_selector: Option<Selector>,
+    //When `None`, the first `<a href>` in the item (or the item itself) is used.
+    pub link_selector: Option<Selector>,
+    pub date_selector: Option<Selector>,
+    pub summary_selector: Option<Selector>,
+}
+
+#[derive(Debug)]
+pub struct ScrapedPage {
+    //`<title>` of the page
+    pub title: String,
+    pub link: String,
+    pub entry_list: Vec<ScrapedEntry>,
+}
+
+#[derive(Debug)]
+pub struct ScrapedEntry {
+    pub title: Option<String>,
+    pub link: Option<String>,
+    pub date: Option<String>,
+    //inner HTML
+    pub summary: Option<String>,
+}
+
+impl ScrapeConfig {
+    //Panics when a selector is invalid so that a typo in the config is found early.
+    pub fn parse_selector(s: &str) -> Selector {
+        Selector::parse(s).unwrap_or_else(|e| panic!("Invalid CSS selector `{}`: {:?}", s, e))
+    }
+}
+
+pub fn scrape(html: &str, page_url: &str, scrape_config: &ScrapeConfig) -> ScrapedPage {
+    let document = Html::parse_document(html);
+
+    let base_url: String = document
+        .select(&Selector::parse("base[href]").unwrap())
+        .next()
+        .and_then(|e| e.value().attr("href"))
+        .map(|href| urlutil::resolve(Some(page_url), href))
+        .unwrap_or_else(|| page_url.to_string());
+
+    let title: String = document
+        .select(&Selector::parse("title").unwrap())
+        .next()
+        .map(|e| get_text(&e))
+        .unwrap_or_default();
+
+    let link_selector: Selector = scrape_config
+        .link_selector
+        .clone()
+        .unwrap_or_else(|| Selector::parse("a[href]").unwrap());
+
+    let mut entry_list: Vec<ScrapedEntry> = Vec::new();
+    for item in document.select(&scrape_config.item_selector) {
+        let link_element: Option<ElementRef> = if item.value().attr("href").is_some() {
+            Some(item)
+        } else {
+            item.select(&link_selector).next()
+        };
+        let link: Option<String> = link_element
+            .and_then(|e| e.value().attr("href"))
+            .map(|href| urlutil::resolve(Some(&base_url), href.trim()));
+
+        let title: Option<String> = match &scrape_config.title_selector {
+            Some(selector) => item.select(selector).next().map(|e| get_text(&e)),
+            None => Some(get_text(&link_element.unwrap_or(item))),
+        }
+        .filter(|s| !s.is_empty());
+
+        //`<time datetime="...">` is machine-readable.
+        let date: Option<String> = scrape_config
+            .date_selector
+            .as_ref()
+            .and_then(|selector| item.select(selector).next())
+            .map(|e| match e.value().attr("datetime") {
+                Some(datetime) => datetime.trim().to_string(),
+                None => get_text(&e),
+            })
+            .filter(|s| !s.is_empty());
+
+        let summary: Option<String> = scrape_config
+            .summary_selector
+            .as_ref()
+            .and_then(|selector| item.select(selector).next())
+            .map(|e| e.inner_html().trim().to_string())
+            .filter(|s| !s.is_empty());
+
+        if title.is_none() && link.is_none() {
+            continue;
+        }
+        entry_list.push(ScrapedEntry {
+            title,
+            link,
+            date,
+            summary,
+        });
+    }
+
+    ScrapedPage {
+        title,
+        link: page_url.to_string(),
+        entry_list,
+    }
+}
+
+//Text content with whitespace collapsed.
+fn get_text(e: &ElementRef) -> String {
+    e.text()
+        .collect::<String>()
+        .split_whitespace()
+        .collect::<Vec<&str>>()
+        .join(" ")
+}
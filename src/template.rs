@@ -0,0 +1,641 @@
+use std::collections::HashMap;
+
+use chrono::format::{Item, StrftimeItems};
+
+use super::date;
+use super::html;
+use super::trigger::TriggerInfo;
+
+//Values available in templates, e.g. `title` for `{{ title }}`.
+//Absent values (e.g. `description` of an item without one) are not in the map and are rendered as empty strings.
+#[derive(Debug, Clone, Default)]
+pub struct TemplateContext {
+    variable_map: HashMap<String, String>,
+}
+
+impl TemplateContext {
+    pub fn set(&mut self, name: &str, value: &str) {
+        self.variable_map
+            .insert(name.to_string(), value.to_string());
+    }
+
+    pub fn get(&self, name: &str) -> Option<&str> {
+        self.variable_map.get(name).map(|s| s.as_str())
+    }
+}
+
+impl From<&TriggerInfo> for TemplateContext {
+    fn from(trigger_info: &TriggerInfo) -> Self {
+        let mut ret = TemplateContext::default();
+        for (name, value) in [
+            ("title", trigger_info.get_title()),
+            ("link", trigger_info.get_link()),
+            ("description", trigger_info.get_description()),
+            ("description_html", trigger_info.get_description_html()),
+            ("pub_date", trigger_info.get_pub_date()),
+            ("published", trigger_info.get_published()),
+            ("content", trigger_info.get_content()),
+            ("content_html", trigger_info.get_content_html()),
+            ("comments", trigger_info.get_comments()),
+            ("thumbnail", trigger_info.get_thumbnail()),
+        ] {
+            if let Some(value) = value {
+                ret.set(name, value);
+            }
+        }
+        if !trigger_info.get_authors().is_empty() {
+            ret.set("authors", &trigger_info.get_authors().join(", "));
+        }
+        if !trigger_info.get_categories().is_empty() {
+            ret.set("categories", &trigger_info.get_categories().join(", "));
+        }
+        if let Some(enclosure) = trigger_info.get_enclosures().first() {
+            ret.set("enclosure", enclosure.get_url());
+        }
+        //e.g. an audio file of a podcast
+        if let Some(enclosure) = trigger_info.get_enclosures().iter().find(|e| !e.is_image()) {
+            ret.set("media", enclosure.get_url());
+        }
+        ret
+    }
+}
+
+//`{{ name | filter | filter:argument }}`
+#[derive(Debug, Clone)]
+enum Filter {
+    //Replaces an absent or empty value.
+    Default(String),
+    //Shortens the value to this number of characters, including the trailing `…`.
+    Truncate(usize),
+    //Reformats a date (e.g. `pub_date`) with a `strftime`-like format, in UTC.
+    //Unparsable dates are kept as they are.
+    Date(String),
+    //escaping for the contexts which the value is embedded in
+    Json,
+    Html,
+    Url,
+    Markdown,
+}
+
+impl Filter {
+    fn new(name: &str, argument: Option<String>) -> Result<Self, String> {
+        let get_argument = || {
+            argument
+                .clone()
+                .ok_or(format!("`{}` needs an argument.", name))
+        };
+        let ret = match name {
+            "default" => Filter::Default(get_argument()?),
+            "truncate" => Filter::Truncate(
+                get_argument()?
+                    .parse()
+                    .map_err(|_| format!("Invalid argument of `truncate`: {:?}", argument))?,
+            ),
+            "date" => {
+                let format: String = get_argument()?;
+                if StrftimeItems::new(&format).any(|e| matches!(e, Item::Error)) {
+                    return Err(format!("Invalid date format: {}", format));
+                }
+                Filter::Date(format)
+            }
+            "json" => Filter::Json,
+            "html" => Filter::Html,
+            "url" => Filter::Url,
+            "markdown" => Filter::Markdown,
+            _ => return Err(format!("Unknown filter: `{}`", name)),
+        };
+        if argument.is_some()
+            && !matches!(
+                ret,
+                Filter::Default(_) | Filter::Truncate(_) | Filter::Date(_)
+            )
+        {
+            return Err(format!("`{}` takes no argument.", name));
+        }
+        Ok(ret)
+    }
+
+    fn apply(&self, value: Option<String>) -> Option<String> {
+        match self {
+            Filter::Default(s) => match value {
+                Some(v) if !v.is_empty() => Some(v),
+                _ => Some(s.clone()),
+            },
+            Filter::Truncate(n) => value.map(|v| truncate(&v, *n)),
+            Filter::Date(format) => value.map(|v| match date::parse(&v) {
+                Some(d) => d.format(format).to_string(),
+                None => v,
+            }),
+            Filter::Json => value.map(|v| {
+                let quoted: String = json::stringify(v);
+                quoted[1..quoted.len() - 1].to_string()
+            }),
+            Filter::Html => value.map(|v| html::escape(&v)),
+            Filter::Url => value.map(|v| percent_encode(&v)),
+            Filter::Markdown => value.map(|v| escape_markdown(&v)),
+        }
+    }
+}
+
+fn truncate(s: &str, n: usize) -> String {
+    if s.chars().count() <= n {
+        return s.to_string();
+    }
+    //There is no room even for `…`.
+    if n == 0 {
+        return String::new();
+    }
+    let mut ret: String = s.chars().take(n.saturating_sub(1)).collect();
+    ret.push('…');
+    ret
+}
+
+//Everything other than the unreserved characters of RFC 3986 is encoded, so the result can be used as a query parameter or a path segment.
+fn percent_encode(s: &str) -> String {
+    let mut ret = String::new();
+    for b in s.bytes() {
+        if b.is_ascii_alphanumeric() || matches!(b, b'-' | b'.' | b'_' | b'~') {
+            ret.push(b as char);
+        } else {
+            ret.push_str(&format!("%{:02X}", b));
+        }
+    }
+    ret
+}
+
+//e.g. for Discord, where `*` and `_` in a title would otherwise be rendered as emphasis
+fn escape_markdown(s: &str) -> String {
+    let mut ret = String::new();
+    for c in s.chars() {
+        if matches!(
+            c,
+            '\\' | '*' | '_' | '~' | '`' | '|' | '>' | '#' | '[' | ']'
+        ) {
+            ret.push('\\');
+        }
+        ret.push(c);
+    }
+    ret
+}
+
+//`{% if name %}` is true when the value is present and not empty.
+#[derive(Debug, Clone)]
+struct Condition {
+    name: String,
+    //`{% if not name %}`
+    is_negated: bool,
+}
+
+impl Condition {
+    fn evaluate(&self, context: &TemplateContext) -> bool {
+        context.get(&self.name).is_some_and(|v| !v.is_empty()) != self.is_negated
+    }
+}
+
+#[derive(Debug, Clone)]
+enum Node {
+    Text(String),
+    Variable {
+        name: String,
+        filter_list: Vec<Filter>,
+    },
+    //`{% if a %}...{% elif b %}...{% else %}...{% endif %}`
+    If {
+        branch_list: Vec<(Condition, Vec<Node>)>,
+        else_branch: Vec<Node>,
+    },
+}
+
+#[derive(Debug)]
+enum Token<'a> {
+    Text(&'a str),
+    //inside `{{ }}`
+    Expression(&'a str),
+    //inside `{% %}`
+    Tag(&'a str),
+}
+
+fn tokenize(s: &str) -> Result<Vec<Token<'_>>, String> {
+    let mut ret: Vec<Token> = Vec::new();
+    let mut rest: &str = s;
+    loop {
+        let start: Option<usize> = match (rest.find("{{"), rest.find("{%")) {
+            (Some(a), Some(b)) => Some(a.min(b)),
+            (a, b) => a.or(b),
+        };
+        let start: usize = match start {
+            Some(start) => start,
+            None => {
+                if !rest.is_empty() {
+                    ret.push(Token::Text(rest));
+                }
+                return Ok(ret);
+            }
+        };
+        if start > 0 {
+            ret.push(Token::Text(&rest[..start]));
+        }
+        let is_expression: bool = rest[start..].starts_with("{{");
+        let closing: &str = if is_expression { "}}" } else { "%}" };
+        let end: usize = rest[start + 2..].find(closing).ok_or_else(|| {
+            format!(
+                "`{}` at {} is not closed.",
+                &rest[start..start + 2],
+                s.len() - rest.len() + start
+            )
+        })? + start
+            + 2;
+        let inner: &str = rest[start + 2..end].trim();
+        ret.push(if is_expression {
+            Token::Expression(inner)
+        } else {
+            Token::Tag(inner)
+        });
+        rest = &rest[end + 2..];
+    }
+}
+
+fn parse_name(s: &str) -> Result<String, String> {
+    if s.is_empty() || !s.chars().all(|c| c.is_ascii_alphanumeric() || c == '_') {
+        return Err(format!("Invalid variable name: `{}`", s));
+    }
+    Ok(s.to_string())
+}
+
+//`name | filter | filter:argument`, where an argument may be quoted (e.g. `date:"%Y-%m-%d %H:%M"`) to contain `|` or spaces.
+fn parse_expression(s: &str) -> Result<Node, String> {
+    let mut part_list: Vec<String> = Vec::new();
+    let mut part = String::new();
+    let mut is_quoted: bool = false;
+    let mut chars = s.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            '"' => {
+                is_quoted = !is_quoted;
+                part.push(c);
+            }
+            '\\' if is_quoted => {
+                part.push(c);
+                if let Some(c) = chars.next() {
+                    part.push(c);
+                }
+            }
+            '|' if !is_quoted => part_list.push(std::mem::take(&mut part)),
+            _ => part.push(c),
+        }
+    }
+    if is_quoted {
+        return Err(format!("Unclosed quote in `{}`", s));
+    }
+    part_list.push(part);
+
+    let name: String = parse_name(part_list[0].trim())?;
+    let mut filter_list: Vec<Filter> = Vec::new();
+    for part in &part_list[1..] {
+        let part: &str = part.trim();
+        let (filter_name, argument) = match part.split_once(':') {
+            Some((n, a)) => (n.trim(), Some(parse_argument(a.trim())?)),
+            None => (part, None),
+        };
+        filter_list.push(Filter::new(filter_name, argument)?);
+    }
+    Ok(Node::Variable { name, filter_list })
+}
+
+fn parse_argument(s: &str) -> Result<String, String> {
+    if !s.starts_with('"') {
+        return Ok(s.to_string());
+    }
+    //A quoted argument is a JSON string literal, so `\"` and `\n` work as expected.
+    json::parse(s)
+        .ok()
+        .and_then(|v| v.as_str().map(|s| s.to_string()))
+        .ok_or_else(|| format!("Invalid argument: {}", s))
+}
+
+fn parse_condition(s: &str) -> Result<Condition, String> {
+    match s.strip_prefix("not ") {
+        Some(name) => Ok(Condition {
+            name: parse_name(name.trim())?,
+            is_negated: true,
+        }),
+        None => Ok(Condition {
+            name: parse_name(s)?,
+            is_negated: false,
+        }),
+    }
+}
+
+//Parses nodes until one of `terminator_list` (e.g. `else`) is found, which is returned together.
+fn parse_nodes<'a>(
+    token_list: &mut std::slice::Iter<Token<'a>>,
+    terminator_list: &[&str],
+) -> Result<(Vec<Node>, Option<&'a str>), String> {
+    let mut ret: Vec<Node> = Vec::new();
+    while let Some(token) = token_list.next() {
+        match token {
+            Token::Text(s) => ret.push(Node::Text(s.to_string())),
+            Token::Expression(s) => ret.push(parse_expression(s)?),
+            Token::Tag(s) => {
+                let keyword: &str = s.split_whitespace().next().unwrap_or_default();
+                if terminator_list.contains(&keyword) {
+                    if matches!(keyword, "else" | "endif") && *s != keyword {
+                        return Err(format!("`{{% {} %}}` takes no argument.", keyword));
+                    }
+                    return Ok((ret, Some(s)));
+                }
+                match keyword {
+                    "if" => {
+                        let mut branch_list: Vec<(Condition, Vec<Node>)> = Vec::new();
+                        let mut else_branch: Vec<Node> = Vec::new();
+                        let mut condition: Condition = parse_condition(s[2..].trim())?;
+                        loop {
+                            let (node_list, terminator) =
+                                parse_nodes(token_list, &["elif", "else", "endif"])?;
+                            branch_list.push((condition, node_list));
+                            match terminator {
+                                Some(t) if t.starts_with("elif") => {
+                                    condition = parse_condition(t[4..].trim())?;
+                                }
+                                Some("else") => {
+                                    let (node_list, terminator) =
+                                        parse_nodes(token_list, &["endif"])?;
+                                    if terminator.is_none() {
+                                        return Err("`{% if %}` is not closed.".to_string());
+                                    }
+                                    else_branch = node_list;
+                                    break;
+                                }
+                                Some("endif") => break,
+                                _ => return Err("`{% if %}` is not closed.".to_string()),
+                            }
+                        }
+                        ret.push(Node::If {
+                            branch_list,
+                            else_branch,
+                        });
+                    }
+                    _ => return Err(format!("Unexpected tag: `{{% {} %}}`", s)),
+                }
+            }
+        }
+    }
+    Ok((ret, None))
+}
+
+fn render_nodes(node_list: &[Node], context: &TemplateContext, ret: &mut String) {
+    for node in node_list {
+        match node {
+            Node::Text(s) => ret.push_str(s),
+            Node::Variable { name, filter_list } => {
+                let value: Option<String> = filter_list
+                    .iter()
+                    .fold(context.get(name).map(|s| s.to_string()), |v, f| f.apply(v));
+                ret.push_str(&value.unwrap_or_default());
+            }
+            Node::If {
+                branch_list,
+                else_branch,
+            } => {
+                let node_list: &[Node] = branch_list
+                    .iter()
+                    .find(|(condition, _)| condition.evaluate(context))
+                    .map(|(_, node_list)| node_list.as_slice())
+                    .unwrap_or(else_branch);
+                render_nodes(node_list, context, ret);
+            }
+        }
+    }
+}
+
+//A text with placeholders such as `Title: {{ title | truncate:100 }}{% if media %}\nMedia: {{ media }}{% endif %}`.
+#[derive(Debug, Clone)]
+pub struct Template {
+    node_list: Vec<Node>,
+}
+
+impl Template {
+    //Panics when the template is malformed so that a typo in the config is found early.
+    pub fn new(s: &str) -> Self {
+        Template::parse(s).unwrap_or_else(|e| panic!("Invalid template `{}`: {}", s, e))
+    }
+
+    pub fn parse(s: &str) -> Result<Self, String> {
+        let token_list: Vec<Token> = tokenize(s)?;
+        let (node_list, terminator) = parse_nodes(&mut token_list.iter(), &[])?;
+        assert!(terminator.is_none());
+        Ok(Template { node_list })
+    }
+
+    pub fn render(&self, context: &TemplateContext) -> String {
+        let mut ret = String::new();
+        render_nodes(&self.node_list, context, &mut ret);
+        ret
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::discord;
+    use super::super::feedconfig::FeedConfig;
+    use super::super::rss::Rss;
+    use super::super::twitter;
+    use super::*;
+
+    fn render(template: &str, variable_list: &[(&str, &str)]) -> String {
+        let mut context = TemplateContext::default();
+        for (name, value) in variable_list {
+            context.set(name, value);
+        }
+        Template::parse(template).unwrap().render(&context)
+    }
+
+    fn parse_trigger_info_list(item_list: &str) -> Vec<TriggerInfo> {
+        let xml: String = format!(
+            r#"<?xml version="1.0"?><rss version="2.0"><channel><title>t</title><link>https://example.com/</link><description>d</description>{}</channel></rss>"#,
+            item_list
+        );
+        let rss = Rss::new(&xml, &FeedConfig::new("https://example.com/feed.xml"));
+        rss.get_item_list().iter().map(TriggerInfo::from).collect()
+    }
+
+    const ITEM_LIST: &str = r#"
+        <item><title>Full *item*</title><link>https://example.com/1</link><pubDate>Mon, 19 Oct 2026 10:30:00 +0900</pubDate>
+            <enclosure url="https://example.com/1.png" type="image/png" length="1"/>
+            <enclosure url="https://example.com/1.mp3" type="audio/mpeg" length="1"/></item>
+        <item><title>Image only</title><link>https://example.com/2</link>
+            <enclosure url="https://example.com/2.png" type="image/png" length="1"/></item>
+        <item><title>Podcast</title>
+            <enclosure url="https://example.com/3.mp3" type="audio/mpeg" length="1"/></item>
+        <item><description>No title</description></item>
+    "#;
+
+    //the format used before templates were introduced
+    fn render_discord_legacy(trigger_info: &TriggerInfo) -> String {
+        let mut content = format!(
+            "--------------------\nTitle: {}\nLink: {}\nDate: {}",
+            trigger_info.get_title().as_ref().unwrap_or(&String::new()),
+            trigger_info.get_link().as_ref().unwrap_or(&String::new()),
+            trigger_info
+                .get_pub_date()
+                .as_ref()
+                .unwrap_or(&String::new()),
+        );
+        if let Some(enclosure) = trigger_info.get_enclosures().iter().find(|e| !e.is_image()) {
+            content += &format!("\nMedia: {}", enclosure.get_url());
+        }
+        content
+    }
+
+    //the format used before templates were introduced
+    fn render_twitter_legacy(trigger_info: &TriggerInfo) -> String {
+        let link: String = trigger_info.get_link().clone().unwrap_or_else(|| {
+            trigger_info
+                .get_enclosures()
+                .first()
+                .map(|e| e.get_url().to_string())
+                .unwrap_or_default()
+        });
+        format!(
+            "{}\n{}",
+            trigger_info.get_title().as_ref().unwrap_or(&String::new()),
+            link,
+        )
+    }
+
+    #[test]
+    fn test_default_templates_match_legacy_formats() {
+        let discord_template = Template::new(discord::DEFAULT_TEMPLATE);
+        let twitter_template = Template::new(twitter::DEFAULT_TEMPLATE);
+        let trigger_info_list: Vec<TriggerInfo> = parse_trigger_info_list(ITEM_LIST);
+        assert_eq!(4, trigger_info_list.len());
+        for trigger_info in trigger_info_list.iter().chain(
+            [TriggerInfo::new(
+                &Some("Feed unhealthy".to_string()),
+                &Some("https://example.com/feed.xml".to_string()),
+                &Some("description".to_string()),
+                &None,
+            )]
+            .iter(),
+        ) {
+            let context = TemplateContext::from(trigger_info);
+            assert_eq!(
+                render_discord_legacy(trigger_info),
+                discord_template.render(&context)
+            );
+            assert_eq!(
+                render_twitter_legacy(trigger_info),
+                twitter_template.render(&context)
+            );
+        }
+    }
+
+    #[test]
+    fn test_malformed_templates_are_rejected() {
+        for template in [
+            "{% endif %}",
+            "{% else %}",
+            "{% elif title %}",
+            "{% if title %}",
+            "{% if title %}a{% else %}b",
+            "{% if title %}a{% else x %}b{% endif %}",
+            "{% if title %}a{% endif x %}",
+            "{% if title %}a{% else %}b{% elif link %}c{% endif %}",
+            "{% if %}a{% endif %}",
+            "{% for x %}",
+            "{{ title | default:\"abc }}",
+            "{{ title | truncate:\"1 }}",
+            "{{ title",
+            "{% if title",
+            "{{ }}",
+            "{{ title link }}",
+            "{{ title | unknown }}",
+            "{{ title | truncate }}",
+            "{{ title | truncate:x }}",
+            "{{ title | truncate:-1 }}",
+            "{{ title | html:x }}",
+            "{{ title | date:\"%Q\" }}",
+        ] {
+            assert!(Template::parse(template).is_err(), "{}", template);
+        }
+    }
+
+    #[test]
+    fn test_conditionals() {
+        let template = "{% if title %}T{% elif not link %}no link{% else %}L{% endif %}";
+        assert_eq!("T", render(template, &[("title", "a"), ("link", "b")]));
+        assert_eq!("L", render(template, &[("title", ""), ("link", "b")]));
+        assert_eq!("no link", render(template, &[]));
+        assert_eq!(
+            "ab",
+            render(
+                "{% if a %}a{% if b %}b{% endif %}{% endif %}",
+                &[("a", "1"), ("b", "1")]
+            )
+        );
+    }
+
+    #[test]
+    fn test_truncate() {
+        assert_eq!("", render("{{ t | truncate:0 }}", &[("t", "abc")]));
+        assert_eq!("", render("{{ t | truncate:0 }}", &[("t", "")]));
+        assert_eq!("…", render("{{ t | truncate:1 }}", &[("t", "abc")]));
+        assert_eq!("a…", render("{{ t | truncate:2 }}", &[("t", "abc")]));
+        assert_eq!("abc", render("{{ t | truncate:3 }}", &[("t", "abc")]));
+        assert_eq!("abc", render("{{ t | truncate:\"10\" }}", &[("t", "abc")]));
+        //counted in characters rather than bytes
+        assert_eq!(
+            "日本…",
+            render("{{ t | truncate:3 }}", &[("t", "日本語の題名")])
+        );
+        assert_eq!("日本語", render("{{ t | truncate:3 }}", &[("t", "日本語")]));
+        assert_eq!("👍…", render("{{ t | truncate:2 }}", &[("t", "👍👍👍")]));
+        assert_eq!("", render("{{ t | truncate:3 }}", &[]));
+    }
+
+    #[test]
+    fn test_filters() {
+        assert_eq!("-", render("{{ t | default:\"-\" }}", &[]));
+        assert_eq!("-", render("{{ t | default:\"-\" }}", &[("t", "")]));
+        assert_eq!("a | b", render("{{ t | default:\"a | b\" }}", &[]));
+        assert_eq!("x", render("{{ t | default:\"-\" }}", &[("t", "x")]));
+        assert_eq!(
+            "2026/10/19 01:30",
+            render(
+                "{{ d | date:\"%Y/%m/%d %H:%M\" }}",
+                &[("d", "Mon, 19 Oct 2026 10:30:00 +0900")]
+            )
+        );
+        assert_eq!(
+            "not a date",
+            render("{{ d | date:\"%Y\" }}", &[("d", "not a date")])
+        );
+        assert_eq!(
+            r#"a\"b\n\u0001"#,
+            render("{{ t | json }}", &[("t", "a\"b\n\u{1}")])
+        );
+        assert_eq!(
+            "&lt;a href=&quot;x&quot;&gt;&amp;&#39;",
+            render("{{ t | html }}", &[("t", "<a href=\"x\">&'")])
+        );
+        assert_eq!(
+            "a%20b%2Fc%3Fd%3De%26-._~%E6%97%A5",
+            render("{{ t | url }}", &[("t", "a b/c?d=e&-._~日")])
+        );
+        assert_eq!(
+            r"\*bold\* \_x\_ \[l\]",
+            render("{{ t | markdown }}", &[("t", "*bold* _x_ [l]")])
+        );
+        assert_eq!(
+            "a&amp;bcd…",
+            render("{{ t | truncate:6 | html }}", &[("t", "a&bcdefg")])
+        );
+    }
+
+    #[test]
+    fn test_text_without_placeholders() {
+        assert_eq!("", render("", &[]));
+        assert_eq!("a { b } % c", render("a { b } % c", &[]));
+        assert_eq!("日本語 x", render("日本語 {{ t }}", &[("t", "x")]));
+    }
+}
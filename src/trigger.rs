@@ -1,5 +1,12 @@
+use std::collections::HashMap;
 use std::error::Error;
 
+use super::atom::AtomEntry;
+use super::html;
+use super::media::Enclosure;
+use super::rss::RssItem;
+use super::template::Template;
+
 pub trait Trigger {
     fn pull_trigger(&self, trigger_info: &TriggerInfo) -> Result<(), Box<dyn Error>>;
 }
@@ -9,7 +16,18 @@ pub struct TriggerInfo {
     title: Option<String>,
     link: Option<String>,
     description: Option<String>,
+    description_html: Option<String>,
     pub_date: Option<String>,
+    published: Option<String>,
+    authors: Vec<String>,
+    categories: Vec<String>,
+    content: Option<String>,
+    content_html: Option<String>,
+    comments: Option<String>,
+    enclosures: Vec<Enclosure>,
+    thumbnail: Option<String>,
+    //`templates` of the feed, keyed by trigger name
+    template_map: HashMap<String, Template>,
 }
 
 impl TriggerInfo {
@@ -23,7 +41,17 @@ impl TriggerInfo {
             title: title.clone(),
             link: link.clone(),
             description: description.clone(),
+            description_html: description.as_deref().map(html::escape),
             pub_date: pub_date.clone(),
+            published: None,
+            authors: Vec::new(),
+            categories: Vec::new(),
+            content: None,
+            content_html: None,
+            comments: None,
+            enclosures: Vec::new(),
+            thumbnail: None,
+            template_map: HashMap::new(),
         }
     }
 
@@ -35,11 +63,106 @@ impl TriggerInfo {
         &self.link
     }
 
+    //plain text
     pub fn get_description(&self) -> &Option<String> {
         &self.description
     }
 
+    pub fn get_description_html(&self) -> &Option<String> {
+        &self.description_html
+    }
+
     pub fn get_pub_date(&self) -> &Option<String> {
         &self.pub_date
     }
+
+    pub fn get_published(&self) -> &Option<String> {
+        &self.published
+    }
+
+    pub fn get_authors(&self) -> &Vec<String> {
+        &self.authors
+    }
+
+    pub fn get_categories(&self) -> &Vec<String> {
+        &self.categories
+    }
+
+    //plain text
+    pub fn get_content(&self) -> &Option<String> {
+        &self.content
+    }
+
+    pub fn get_content_html(&self) -> &Option<String> {
+        &self.content_html
+    }
+
+    pub fn get_comments(&self) -> &Option<String> {
+        &self.comments
+    }
+
+    pub fn get_enclosures(&self) -> &Vec<Enclosure> {
+        &self.enclosures
+    }
+
+    pub fn get_thumbnail(&self) -> &Option<String> {
+        &self.thumbnail
+    }
+
+    //the template of the feed for the trigger, which takes precedence over the one of the trigger
+    pub fn get_template(&self, trigger_name: &str) -> Option<&Template> {
+        self.template_map.get(trigger_name)
+    }
+}
+
+impl From<&RssItem> for TriggerInfo {
+    //RSS `<description>` is usually an entity-encoded HTML.
+    fn from(rss_item: &RssItem) -> Self {
+        TriggerInfo {
+            title: rss_item.get_title().clone(),
+            link: rss_item.get_link().clone(),
+            description: rss_item
+                .get_description()
+                .as_deref()
+                .map(html::to_plain_text),
+            description_html: rss_item.get_description().clone(),
+            pub_date: rss_item.get_pub_date().clone(),
+            published: rss_item.get_pub_date().clone(),
+            authors: rss_item.get_authors().clone(),
+            categories: rss_item.get_categories().clone(),
+            content: rss_item.get_content().as_deref().map(html::to_plain_text),
+            content_html: rss_item.get_content().clone(),
+            comments: rss_item.get_comments().clone(),
+            enclosures: rss_item.get_enclosures().clone(),
+            thumbnail: rss_item.get_thumbnail().clone(),
+            template_map: rss_item.get_feed_config().template_map.clone(),
+        }
+    }
+}
+
+impl From<&AtomEntry> for TriggerInfo {
+    fn from(atom_entry: &AtomEntry) -> Self {
+        TriggerInfo {
+            title: Some(atom_entry.get_title().to_string()),
+            link: Some(atom_entry.get_link().to_string()),
+            description: atom_entry
+                .get_summary_or_content()
+                .map(|e| e.get_plain_text().to_string()),
+            description_html: atom_entry
+                .get_summary_or_content()
+                .map(|e| e.get_html().to_string()),
+            pub_date: Some(atom_entry.get_updated().to_string()),
+            published: atom_entry.get_published().clone(),
+            authors: atom_entry.get_authors().clone(),
+            categories: atom_entry.get_categories().clone(),
+            content: atom_entry
+                .get_content()
+                .map(|e| e.get_plain_text().to_string()),
+            content_html: atom_entry.get_content().map(|e| e.get_html().to_string()),
+            comments: None,
+            enclosures: atom_entry.get_enclosures().clone(),
+            thumbnail: atom_entry.get_thumbnail().clone(),
+            template_map: atom_entry.get_feed_config().template_map.clone(),
+        }
+    }
 }
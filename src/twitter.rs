@@ -3,17 +3,29 @@ use std::error::Error;
 use log::*;
 
 use super::command;
+use super::template::Template;
+use super::template::TemplateContext;
 use super::trigger::Trigger;
 use super::trigger::TriggerInfo;
 
-pub struct TwitterNotification;
+//Some podcast feeds have only enclosures without links.
+pub const DEFAULT_TEMPLATE: &str =
+    "{{ title }}\n{% if link %}{{ link }}{% else %}{{ enclosure }}{% endif %}";
+
+pub struct TwitterNotification {
+    //name of the trigger in the config, used to look up `templates` of feeds
+    name: String,
+    template: Template,
+}
 
 const SCRIPT_PATH: &str = "./twitter/tweet.py";
 
 impl TwitterNotification {
-    #[allow(clippy::new_without_default)]
-    pub fn new() -> Self {
-        TwitterNotification {}
+    pub fn new(name: &str, template: Template) -> Self {
+        TwitterNotification {
+            name: name.to_string(),
+            template,
+        }
     }
 }
 
@@ -23,13 +35,12 @@ impl Trigger for TwitterNotification {
 
         let command = "python3";
         let args = vec![SCRIPT_PATH];
-        let input = format!(
-            "{}\n{}",
-            trigger_info.get_title().as_ref().unwrap_or(&String::new()),
-            trigger_info.get_link().as_ref().unwrap_or(&String::new()),
-        );
+        let input: String = trigger_info
+            .get_template(&self.name)
+            .unwrap_or(&self.template)
+            .render(&TemplateContext::from(trigger_info));
 
-        let result = command::run(command, &args, &input);
+        let result = command::run(command, &args, &input)?;
 
         if result.exit_status == 0 {
             debug!("TwitterNotification success");
@@ -37,7 +48,7 @@ impl Trigger for TwitterNotification {
         } else {
             debug!("TwitterNotification failed:");
             if !result.stdout.is_empty() {
-                debug!("{}", result.stdout);
+                debug!("{}", String::from_utf8_lossy(&result.stdout));
             }
             if !result.stderr.is_empty() {
                 debug!("{}", result.stderr);
@@ -0,0 +1,173 @@
+use url::Url;
+
+//Resolves a (possibly relative) URL against `base`.
+//`url` is returned as is when it is already absolute or when it cannot be resolved.
+pub fn resolve(base: Option<&str>, url: &str) -> String {
+    if Url::parse(url).is_ok() {
+        return url.to_string();
+    }
+    match base.and_then(|base| Url::parse(base).ok()) {
+        Some(base) => match base.join(url) {
+            Ok(resolved) => resolved.to_string(),
+            Err(_) => url.to_string(),
+        },
+        None => url.to_string(),
+    }
+}
+
+pub fn is_absolute(url: &str) -> bool {
+    Url::parse(url).is_ok()
+}
+
+//Query parameters which only track where a visitor came from.
+pub const DEFAULT_STRIPPED_QUERY_PARAMETER_LIST: &[&str] = &[
+    "utm_*", "fbclid", "gclid", "dclid", "msclkid", "yclid", "mc_cid", "mc_eid", "_hsenc", "_hsmi",
+    "igshid",
+];
+
+#[derive(Debug, Clone)]
+pub struct UrlCanonicalization {
+    //Names of the query parameters to strip. A trailing `*` matches any suffix (e.g. `utm_*`).
+    pub stripped_query_parameter_list: Vec<String>,
+    pub should_remove_trailing_slash: bool,
+}
+
+impl UrlCanonicalization {
+    pub fn new() -> Self {
+        UrlCanonicalization {
+            stripped_query_parameter_list: DEFAULT_STRIPPED_QUERY_PARAMETER_LIST
+                .iter()
+                .map(|s| s.to_string())
+                .collect(),
+            should_remove_trailing_slash: true,
+        }
+    }
+
+    fn should_strip(&self, name: &str) -> bool {
+        self.stripped_query_parameter_list
+            .iter()
+            .any(|pattern| match pattern.strip_suffix('*') {
+                Some(prefix) => name.starts_with(prefix),
+                None => name == pattern,
+            })
+    }
+}
+
+impl Default for UrlCanonicalization {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+//Normalizes a URL so that the same page always has the same URL.
+//The scheme and the host are lowercased and the default port is removed (these are done by `Url` itself),
+// the listed query parameters are stripped, and the trailing slash of the path is optionally removed.
+//`url` is returned as is when it cannot be parsed.
+pub fn canonicalize(url: &str, canonicalization: &UrlCanonicalization) -> String {
+    let mut url: Url = match Url::parse(url) {
+        Ok(url) => url,
+        Err(_) => return url.to_string(),
+    };
+
+    //We filter the raw query instead of using `query_pairs()` so that the remaining parameters are kept byte-for-byte.
+    if let Some(query) = url.query() {
+        let query: String = query
+            .split('&')
+            .filter(|pair| !pair.is_empty())
+            .filter(|pair| !canonicalization.should_strip(pair.split('=').next().unwrap()))
+            .collect::<Vec<&str>>()
+            .join("&");
+        url.set_query(if query.is_empty() { None } else { Some(&query) });
+    }
+
+    if canonicalization.should_remove_trailing_slash
+        && url.path().len() > 1
+        && url.path().ends_with('/')
+    {
+        let path: String = url.path().trim_end_matches('/').to_string();
+        url.set_path(if path.is_empty() { "/" } else { &path });
+    }
+
+    url.to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_resolve() {
+        let base = Some("https://example.com/blog/feed.xml");
+        assert_eq!("https://example.com/blog/1.html", resolve(base, "1.html"));
+        assert_eq!("https://example.com/1.html", resolve(base, "/1.html"));
+        assert_eq!("https://example.com/1.html", resolve(base, "../1.html"));
+        assert_eq!(
+            "https://other.example/1",
+            resolve(base, "//other.example/1")
+        );
+        assert_eq!(
+            "https://example.com/blog/feed.xml?page=2",
+            resolve(base, "?page=2")
+        );
+        //already absolute
+        assert_eq!(
+            "http://other.example/1",
+            resolve(base, "http://other.example/1")
+        );
+        assert_eq!(
+            "mailto:a@example.com",
+            resolve(base, "mailto:a@example.com")
+        );
+        //cannot be resolved
+        assert_eq!("1.html", resolve(None, "1.html"));
+        assert_eq!("1.html", resolve(Some("not a url"), "1.html"));
+    }
+
+    #[test]
+    fn test_is_absolute() {
+        assert!(is_absolute("https://example.com/"));
+        assert!(!is_absolute("/1.html"));
+        assert!(!is_absolute("1.html"));
+    }
+
+    #[test]
+    fn test_canonicalize() {
+        let canonicalization = UrlCanonicalization::new();
+        assert_eq!(
+            "https://example.com/a?id=1&b=%2F",
+            canonicalize(
+                "HTTPS://Example.COM:443/a/?utm_source=x&id=1&fbclid=y&&b=%2F&utm_medium",
+                &canonicalization
+            )
+        );
+        assert_eq!(
+            "https://example.com/a",
+            canonicalize("https://example.com/a//?utm_source=x", &canonicalization)
+        );
+        //The root path is kept.
+        assert_eq!(
+            "https://example.com/",
+            canonicalize("https://example.com/", &canonicalization)
+        );
+        //cannot be parsed
+        assert_eq!(
+            "/a/?utm_source=x",
+            canonicalize("/a/?utm_source=x", &canonicalization)
+        );
+    }
+
+    #[test]
+    fn test_canonicalize_with_custom_settings() {
+        let canonicalization = UrlCanonicalization {
+            stripped_query_parameter_list: vec!["ref".to_string(), "session*".to_string()],
+            should_remove_trailing_slash: false,
+        };
+        assert_eq!(
+            "https://example.com/a/?utm_source=x&reference=1",
+            canonicalize(
+                "https://example.com/a/?utm_source=x&ref=y&reference=1&session_id=z",
+                &canonicalization
+            )
+        );
+    }
+}
@@ -0,0 +1,196 @@
+use std::error::Error;
+
+use json::JsonValue;
+use log::*;
+use reqwest::blocking::{Client, RequestBuilder, Response};
+use reqwest::Method;
+use url::form_urlencoded;
+
+use super::http;
+use super::http::HttpOptions;
+use super::template::Template;
+use super::template::TemplateContext;
+use super::trigger::Trigger;
+use super::trigger::TriggerInfo;
+
+#[derive(Debug, Clone)]
+pub enum WebhookBody {
+    None,
+    //Values are inserted as they are, so they need not be escaped.
+    Json(JsonTemplate),
+    //`application/x-www-form-urlencoded`; each value is a template
+    Form(Vec<(String, Template)>),
+    //`text/plain` unless `Content-Type` is specified in `headers`
+    Text(Template),
+}
+
+impl WebhookBody {
+    //`body_type` is one of `json`, `form` and `text`.
+    pub fn new(body_type: &str, body: &JsonValue) -> Self {
+        match (body_type, body) {
+            (_, JsonValue::Null) => WebhookBody::None,
+            ("json", v) => WebhookBody::Json(JsonTemplate::new(v)),
+            ("form", JsonValue::Object(o)) => WebhookBody::Form(
+                o.iter()
+                    .map(|(k, v)| (k.to_string(), Template::new(v.as_str().unwrap())))
+                    .collect(),
+            ),
+            ("text", v) => WebhookBody::Text(Template::new(v.as_str().unwrap())),
+            _ => panic!("Invalid `body` of the body type `{}`: {}", body_type, body),
+        }
+    }
+}
+
+//A JSON value each of whose strings (including nested ones) is a template.
+#[derive(Debug, Clone)]
+pub enum JsonTemplate {
+    String(Template),
+    Array(Vec<JsonTemplate>),
+    Object(Vec<(String, JsonTemplate)>),
+    //numbers, booleans and `null`
+    Other(JsonValue),
+}
+
+impl JsonTemplate {
+    pub fn new(v: &JsonValue) -> Self {
+        match v {
+            JsonValue::String(_) | JsonValue::Short(_) => {
+                JsonTemplate::String(Template::new(v.as_str().unwrap()))
+            }
+            JsonValue::Array(l) => JsonTemplate::Array(l.iter().map(JsonTemplate::new).collect()),
+            JsonValue::Object(o) => JsonTemplate::Object(
+                o.iter()
+                    .map(|(k, v)| (k.to_string(), JsonTemplate::new(v)))
+                    .collect(),
+            ),
+            _ => JsonTemplate::Other(v.clone()),
+        }
+    }
+
+    pub fn render(&self, context: &TemplateContext) -> JsonValue {
+        match self {
+            JsonTemplate::String(t) => JsonValue::from(t.render(context)),
+            JsonTemplate::Array(l) => {
+                JsonValue::Array(l.iter().map(|e| e.render(context)).collect())
+            }
+            JsonTemplate::Object(l) => {
+                let mut ret = JsonValue::new_object();
+                for (k, v) in l {
+                    ret[k.as_str()] = v.render(context);
+                }
+                ret
+            }
+            JsonTemplate::Other(v) => v.clone(),
+        }
+    }
+}
+
+//Rendered into the variable `message`, which can be used in `body`.
+pub const DEFAULT_TEMPLATE: &str = "{{ title }}\n{{ link }}";
+
+//Sends an HTTP request to an arbitrary endpoint (e.g. an internal chat service).
+pub struct WebhookNotification {
+    //name of the trigger in the config, used to look up `templates` of feeds
+    name: String,
+    template: Template,
+    client: Client,
+    method: Method,
+    url: String,
+    header_list: Vec<(String, String)>,
+    body: WebhookBody,
+    //When empty, any `2xx` is regarded as success.
+    success_status_code_list: Vec<u16>,
+    http_options: HttpOptions,
+}
+
+impl WebhookNotification {
+    #[allow(clippy::too_many_arguments)]
+    pub fn new(
+        name: &str,
+        template: Template,
+        method: &str,
+        url: &str,
+        header_list: Vec<(String, String)>,
+        body: WebhookBody,
+        success_status_code_list: Vec<u16>,
+        http_options: &HttpOptions,
+    ) -> Self {
+        WebhookNotification {
+            name: name.to_string(),
+            template,
+            client: http::get_client(&http_options.client_options).unwrap(),
+            method: Method::from_bytes(method.to_ascii_uppercase().as_bytes())
+                .unwrap_or_else(|_| panic!("Invalid HTTP method: {}", method)),
+            url: url.to_string(),
+            header_list,
+            body,
+            success_status_code_list,
+            http_options: http_options.clone(),
+        }
+    }
+
+    fn is_success(&self, status: u16) -> bool {
+        if self.success_status_code_list.is_empty() {
+            (200..300).contains(&status)
+        } else {
+            self.success_status_code_list.contains(&status)
+        }
+    }
+}
+
+impl Trigger for WebhookNotification {
+    fn pull_trigger(&self, trigger_info: &TriggerInfo) -> Result<(), Box<dyn Error>> {
+        debug!("WebhookNotification start: {:?}", trigger_info);
+
+        let mut context = TemplateContext::from(trigger_info);
+        let message: String = trigger_info
+            .get_template(&self.name)
+            .unwrap_or(&self.template)
+            .render(&context);
+        context.set("message", &message);
+
+        let mut request: RequestBuilder = self
+            .client
+            .request(self.method.clone(), &self.url)
+            .header("User-Agent", &self.http_options.user_agent)
+            .timeout(self.http_options.timeout);
+        let has_content_type: bool = self
+            .header_list
+            .iter()
+            .any(|(n, _)| n.eq_ignore_ascii_case("Content-Type"));
+        let (body, default_content_type): (Option<String>, &str) = match &self.body {
+            WebhookBody::None => (None, ""),
+            WebhookBody::Json(v) => (Some(v.render(&context).dump()), "application/json"),
+            //`RequestBuilder::form()` is not used as it always sets `Content-Type`.
+            WebhookBody::Form(l) => (
+                Some(
+                    form_urlencoded::Serializer::new(String::new())
+                        .extend_pairs(l.iter().map(|(k, v)| (k, v.render(&context))))
+                        .finish(),
+                ),
+                "application/x-www-form-urlencoded",
+            ),
+            WebhookBody::Text(t) => (Some(t.render(&context)), "text/plain; charset=utf-8"),
+        };
+        if let Some(body) = body {
+            request = request.body(body);
+            if !has_content_type {
+                request = request.header("Content-Type", default_content_type);
+            }
+        }
+        for (name, value) in &self.header_list {
+            request = request.header(name, value);
+        }
+
+        let res: Response = request.send()?;
+        let status: u16 = res.status().as_u16();
+        if self.is_success(status) {
+            debug!("WebhookNotification success");
+            Ok(())
+        } else {
+            let text: String = res.text()?;
+            debug!("WebhookNotification failed: {} {}", status, text);
+            Err(format!("Webhook responded with {}: {}", status, text).into())
+        }
+    }
+}
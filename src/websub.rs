@@ -0,0 +1,433 @@
+use std::error::Error;
+use std::io::Read;
+use std::panic::{self, AssertUnwindSafe};
+use std::time::Duration;
+
+use chrono::{DateTime, Utc};
+use hmac::{Hmac, Mac};
+use log::*;
+use sha1::Sha1;
+use sha2::{Sha256, Sha384, Sha512};
+use tiny_http::{Header, Method, Request, Response, Server};
+use url::Url;
+
+use super::config::Config;
+use super::database::Database;
+use super::encoding;
+use super::feedconfig::FeedConfig;
+use super::http;
+
+//Subscriptions are renewed this long before they expire.
+//For a short lease, half of it is used instead.
+const RENEWAL_MARGIN: Duration = Duration::from_secs(24 * 60 * 60);
+
+//A subscription which has not been verified (or has been denied) is retried after this.
+const RETRY_INTERVAL: Duration = Duration::from_secs(24 * 60 * 60);
+
+const MAX_CONTENT_SIZE: u64 = 10 * 1024 * 1024;
+
+#[derive(Debug, Clone)]
+pub struct WebSubConfig {
+    //the public URL of the callback server, which is reachable from hubs
+    pub callback_url: String,
+    //the address the callback server listens on (e.g. `0.0.0.0:8080`)
+    pub listen_address: String,
+    //requested to hubs; hubs may choose another value
+    pub lease_duration: Duration,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SubscriptionState {
+    //A subscription request has been sent but the hub has not verified it yet.
+    Pending,
+    Verified,
+    Denied,
+}
+
+impl SubscriptionState {
+    pub fn new(s: &str) -> Self {
+        match s {
+            "pending" => Self::Pending,
+            "verified" => Self::Verified,
+            "denied" => Self::Denied,
+            _ => panic!("Unknown subscription state: {}", s),
+        }
+    }
+
+    pub fn as_str(&self) -> &str {
+        match self {
+            Self::Pending => "pending",
+            Self::Verified => "verified",
+            Self::Denied => "denied",
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Subscription {
+    //`<link rel="self">` of the feed, or `url` in the config if absent
+    pub topic_url: String,
+    pub hub_url: String,
+    //`url` in the config
+    pub feed_url: String,
+    pub secret: String,
+    pub state: SubscriptionState,
+    pub lease_expiration: Option<DateTime<Utc>>,
+    pub update_date: DateTime<Utc>,
+}
+
+//Each topic has its own callback URL so that pushed contents can be associated with the subscription.
+fn get_callback_url(websub_config: &WebSubConfig, topic_url: &str) -> String {
+    let mut url: Url = Url::parse(&websub_config.callback_url).unwrap();
+    url.query_pairs_mut().append_pair("topic", topic_url);
+    url.to_string()
+}
+
+fn is_due(subscription: &Subscription, hub_url: &str) -> bool {
+    if subscription.hub_url != hub_url {
+        return true;
+    }
+    let now: DateTime<Utc> = Utc::now();
+    match subscription.state {
+        //`update_date` is when the hub verified the subscription.
+        SubscriptionState::Verified => match subscription.lease_expiration {
+            Some(lease_expiration) => {
+                let margin: chrono::Duration = chrono::Duration::from_std(RENEWAL_MARGIN)
+                    .unwrap()
+                    .min((lease_expiration - subscription.update_date) / 2);
+                lease_expiration - now < margin
+            }
+            None => false,
+        },
+        SubscriptionState::Pending | SubscriptionState::Denied => {
+            now - subscription.update_date > chrono::Duration::from_std(RETRY_INTERVAL).unwrap()
+        }
+    }
+}
+
+//Subscribes to the topic when not subscribed yet, or renews the subscription when its lease is about to expire.
+pub fn subscribe_if_needed(
+    db: &Database,
+    websub_config: &WebSubConfig,
+    feed_config: &FeedConfig,
+    hub_url: &str,
+    topic_url: &str,
+) {
+    let old_subscription: Option<Subscription> = db.select_websub_subscription(topic_url);
+    if let Some(old_subscription) = &old_subscription {
+        if !is_due(old_subscription, hub_url) {
+            return;
+        }
+    }
+
+    //The secret is kept on renewal so that contents pushed meanwhile are still accepted.
+    let secret: String = match old_subscription {
+        Some(s) if s.hub_url == hub_url => s.secret,
+        _ => {
+            let mut secret: [u8; 20] = [0; 20];
+            getrandom::getrandom(&mut secret).unwrap();
+            hex::encode(secret)
+        }
+    };
+    let subscription = Subscription {
+        topic_url: topic_url.to_string(),
+        hub_url: hub_url.to_string(),
+        feed_url: feed_config.get_url().to_string(),
+        secret,
+        state: SubscriptionState::Pending,
+        lease_expiration: None,
+        update_date: Utc::now(),
+    };
+    //This is recorded before the request as some hubs verify the intent before responding.
+    db.upsert_websub_subscription(&subscription);
+
+    match send_subscription_request(websub_config, feed_config, &subscription) {
+        Ok(()) => info!(
+            "WebSub: requested a subscription to {} via {}",
+            topic_url, hub_url
+        ),
+        Err(e) => warn!(
+            "WebSub: failed to subscribe to {} via {}: {}",
+            topic_url, hub_url, e
+        ),
+    }
+}
+
+//The hub is requested with the proxy and the TLS settings of the feed.
+fn send_subscription_request(
+    websub_config: &WebSubConfig,
+    feed_config: &FeedConfig,
+    subscription: &Subscription,
+) -> Result<(), Box<dyn Error>> {
+    let response = http::get_client(&feed_config.http_options.client_options)?
+        .post(&subscription.hub_url)
+        .form(&[
+            ("hub.mode", "subscribe"),
+            ("hub.topic", &subscription.topic_url),
+            (
+                "hub.callback",
+                &get_callback_url(websub_config, &subscription.topic_url),
+            ),
+            ("hub.secret", &subscription.secret),
+            (
+                "hub.lease_seconds",
+                &websub_config.lease_duration.as_secs().to_string(),
+            ),
+        ])
+        .header("User-Agent", &feed_config.http_options.user_agent)
+        .timeout(feed_config.http_options.timeout)
+        .send()?;
+    if !response.status().is_success() {
+        return Err(format!("{}: {}", response.status(), response.text()?).into());
+    }
+    Ok(())
+}
+
+//Runs the callback server forever.
+//The config file is read for each pushed content so that it is auto-reloaded as in polling.
+pub fn serve(config_file: &str) {
+    let config = Config::new(config_file);
+    let websub_config: &WebSubConfig = config
+        .get_websub_config()
+        .as_ref()
+        .expect("`websub` is not configured.");
+    let server = Server::http(&websub_config.listen_address).unwrap();
+    info!("WebSub: listening on {}", websub_config.listen_address);
+
+    for mut request in server.incoming_requests() {
+        let (status, body): (u16, String) = match panic::catch_unwind(AssertUnwindSafe(|| {
+            handle_request(config_file, &mut request)
+        })) {
+            Ok(v) => v,
+            Err(_) => (500, String::new()),
+        };
+        if let Err(e) = request.respond(Response::from_string(body).with_status_code(status)) {
+            warn!("WebSub: failed to respond: {}", e);
+        }
+    }
+}
+
+fn handle_request(config_file: &str, request: &mut Request) -> (u16, String) {
+    let url: Url = Url::parse("http://localhost")
+        .unwrap()
+        .join(request.url())
+        .unwrap();
+    let get_param = |key: &str| {
+        url.query_pairs()
+            .find(|(k, _)| k == key)
+            .map(|(_, v)| v.to_string())
+    };
+
+    let config = Config::new(config_file);
+    let db = Database::new(config.get_database_file(), false);
+
+    let subscription: Option<Subscription> = get_param("topic")
+        .as_deref()
+        .and_then(|topic_url| db.select_websub_subscription(topic_url));
+
+    match request.method() {
+        //verification of intent (https://www.w3.org/TR/websub/#hub-verifies-intent)
+        Method::Get => {
+            let mode: Option<String> = get_param("hub.mode");
+            match (mode.as_deref(), subscription) {
+                (Some("subscribe"), Some(subscription))
+                    if get_param("hub.topic").as_deref() == Some(&subscription.topic_url) =>
+                {
+                    let lease_expiration: Option<DateTime<Utc>> = get_param("hub.lease_seconds")
+                        .and_then(|s| s.parse::<i64>().ok())
+                        .map(|s| Utc::now() + chrono::Duration::seconds(s));
+                    db.update_websub_subscription_state(
+                        &subscription.topic_url,
+                        SubscriptionState::Verified,
+                        lease_expiration,
+                    );
+                    info!("WebSub: subscribed to {}", subscription.topic_url);
+                    (200, get_param("hub.challenge").unwrap_or_default())
+                }
+                (Some("denied"), Some(subscription)) => {
+                    db.update_websub_subscription_state(
+                        &subscription.topic_url,
+                        SubscriptionState::Denied,
+                        None,
+                    );
+                    warn!(
+                        "WebSub: subscription to {} denied: {}",
+                        subscription.topic_url,
+                        get_param("hub.reason").unwrap_or_default()
+                    );
+                    (200, String::new())
+                }
+                //We don't unsubscribe by ourselves, so this confirms that we no longer need the topic.
+                (Some("unsubscribe"), None) => {
+                    (200, get_param("hub.challenge").unwrap_or_default())
+                }
+                _ => (404, String::new()),
+            }
+        }
+
+        //content distribution (https://www.w3.org/TR/websub/#content-distribution)
+        Method::Post => {
+            let subscription: Subscription = match subscription {
+                Some(s) => s,
+                None => return (410, String::new()),
+            };
+
+            //One extra byte is read to tell a content of exactly `MAX_CONTENT_SIZE` from a larger one.
+            let mut body: Vec<u8> = Vec::new();
+            if request
+                .as_reader()
+                .take(MAX_CONTENT_SIZE + 1)
+                .read_to_end(&mut body)
+                .is_err()
+            {
+                return (400, String::new());
+            }
+            if body.len() as u64 > MAX_CONTENT_SIZE {
+                warn!(
+                    "WebSub: rejected a content of {} larger than {} bytes",
+                    subscription.topic_url, MAX_CONTENT_SIZE
+                );
+                return (413, String::new());
+            }
+
+            //Invalid contents must be ignored but still be acknowledged.
+            let signature: Option<String> = get_header(request, "X-Hub-Signature");
+            if !is_signature_valid(&subscription.secret, signature.as_deref(), &body) {
+                warn!(
+                    "WebSub: ignored a content of {} with an invalid signature",
+                    subscription.topic_url
+                );
+                return (202, String::new());
+            }
+
+            let feed_config: &FeedConfig = match config
+                .get_feed_config_list()
+                .iter()
+                .find(|e| e.get_url() == subscription.feed_url)
+            {
+                Some(e) => e,
+                None => {
+                    warn!(
+                        "WebSub: ignored a content of {} as it is no longer in the config",
+                        subscription.topic_url
+                    );
+                    return (202, String::new());
+                }
+            };
+
+            info!("WebSub: received a content of {}", subscription.topic_url);
+            let xml: String =
+                encoding::decode(&body, get_header(request, "Content-Type").as_deref());
+            if let Err(e) =
+                super::handle_feed(&db, &xml, &subscription.topic_url, &config, feed_config)
+            {
+                warn!("WebSub: {}", e);
+            }
+            (202, String::new())
+        }
+
+        _ => (405, String::new()),
+    }
+}
+
+fn get_header(request: &Request, name: &str) -> Option<String> {
+    request
+        .headers()
+        .iter()
+        .find(|h: &&Header| h.field.as_str().as_str().eq_ignore_ascii_case(name))
+        .map(|h| h.value.to_string())
+}
+
+//`X-Hub-Signature: <method>=<hex>` (https://www.w3.org/TR/websub/#signing-content)
+fn is_signature_valid(secret: &str, signature: Option<&str>, body: &[u8]) -> bool {
+    let (method, signature) = match signature.and_then(|s| s.split_once('=')) {
+        Some(v) => v,
+        None => return false,
+    };
+    let signature: Vec<u8> = match hex::decode(signature.trim()) {
+        Ok(v) => v,
+        Err(_) => return false,
+    };
+    let secret: &[u8] = secret.as_bytes();
+    match method {
+        "sha1" => verify::<Hmac<Sha1>>(secret, body, &signature),
+        "sha256" => verify::<Hmac<Sha256>>(secret, body, &signature),
+        "sha384" => verify::<Hmac<Sha384>>(secret, body, &signature),
+        "sha512" => verify::<Hmac<Sha512>>(secret, body, &signature),
+        _ => false,
+    }
+}
+
+fn verify<M: Mac + hmac::digest::KeyInit>(secret: &[u8], body: &[u8], signature: &[u8]) -> bool {
+    let mut mac = <M as Mac>::new_from_slice(secret).unwrap();
+    mac.update(body);
+    //constant-time comparison
+    mac.verify_slice(signature).is_ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    //HMACs of `<feed/>` with the secret `secret`
+    const SIGNATURE_LIST: [(&str, &str); 4] = [
+        ("sha1", "f38e73e7d772790d36ded9be19b36748b2a27335"),
+        (
+            "sha256",
+            "bc34c1a93cc52a1e89787d5bfca33388be4a3190ed87910472a44e4bbb8122c2",
+        ),
+        (
+            "sha384",
+            "991b82d52d84d0f54f761c9280f24c677925d37e91ac9fb14e89a93aa91f221eb2fea7a97266aadb15dcf06007c6295b",
+        ),
+        (
+            "sha512",
+            "384ccd8dbbe4e9af2519c641f8c9da094d600f81617283772712527e64841de36bb901fbb336a95fe96811d03de0c574537044d4bdcfc9559aa97e4eec5f8c7a",
+        ),
+    ];
+
+    #[test]
+    fn test_is_signature_valid() {
+        for (method, hex) in SIGNATURE_LIST {
+            let signature = format!("{}={}", method, hex);
+            assert!(is_signature_valid("secret", Some(&signature), b"<feed/>"));
+            //Hex digits are case-insensitive.
+            let upper_case_signature = format!("{}={}", method, hex.to_uppercase());
+            assert!(is_signature_valid(
+                "secret",
+                Some(&upper_case_signature),
+                b"<feed/>"
+            ));
+            assert!(!is_signature_valid("Secret", Some(&signature), b"<feed/>"));
+            assert!(!is_signature_valid("secret", Some(&signature), b"<feed />"));
+        }
+    }
+
+    #[test]
+    fn test_is_signature_valid_with_malformed_signatures() {
+        let (_, hex) = SIGNATURE_LIST[1];
+        assert!(!is_signature_valid("secret", None, b"<feed/>"));
+        assert!(!is_signature_valid("secret", Some(hex), b"<feed/>"));
+        assert!(!is_signature_valid(
+            "secret",
+            Some(&format!("md5={}", hex)),
+            b"<feed/>"
+        ));
+        //signed by another algorithm
+        assert!(!is_signature_valid(
+            "secret",
+            Some(&format!("sha512={}", hex)),
+            b"<feed/>"
+        ));
+        assert!(!is_signature_valid(
+            "secret",
+            Some("sha256=xyz"),
+            b"<feed/>"
+        ));
+        assert!(!is_signature_valid(
+            "secret",
+            Some(&format!("sha256={}", &hex[2..])),
+            b"<feed/>"
+        ));
+    }
+}
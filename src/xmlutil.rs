@@ -0,0 +1,195 @@
+use quick_xml::events::BytesStart;
+use regex::Regex;
+
+use super::html;
+
+//Returns the unescaped value of the attribute whose (qualified) name is `key`.
+pub fn get_attribute(e: &BytesStart, key: &[u8]) -> Option<String> {
+    e.attributes()
+        .filter_map(|a| a.ok())
+        .find(|a| a.key == key)
+        .and_then(|a| {
+            a.unescaped_value()
+                .ok()
+                .map(|v| String::from_utf8_lossy(&v).to_string())
+        })
+}
+
+//Repairs common problems of real-world feeds so that they can be parsed as XML:
+// - characters not allowed in XML (e.g. control characters) are removed
+// - HTML named character references (e.g. `&nbsp;`) are replaced with numeric ones
+// - bare `&` is escaped
+//CDATA sections and comments are left as they are.
+//The second element of the return value describes what were repaired.
+pub fn repair(xml: &str) -> (String, Vec<String>) {
+    let mut ret = String::with_capacity(xml.len());
+    let mut num_invalid_characters: (usize, Option<usize>) = (0, None);
+    let mut num_html_entities: (usize, Option<usize>) = (0, None);
+    let mut num_bare_ampersands: (usize, Option<usize>) = (0, None);
+
+    let entity_regex =
+        Regex::new(r"&(#[0-9]+;|#[xX][0-9a-fA-F]+;|([A-Za-z][A-Za-z0-9]*);)?").unwrap();
+
+    let mut repair_segment = |segment: &str, offset: usize, ret: &mut String| {
+        //(offset in the filtered segment, length in bytes) of each removed character,
+        // to report the offsets of the other problems in the original `xml`
+        let mut removed_character_list: Vec<(usize, usize)> = Vec::new();
+        let mut removed_length: usize = 0;
+        let segment: String = segment
+            .char_indices()
+            .filter(|(i, c)| {
+                let is_valid = matches!(c, '\t' | '\n' | '\r')
+                    || !(c.is_control() || *c == '\u{FFFE}' || *c == '\u{FFFF}');
+                if !is_valid {
+                    num_invalid_characters.0 += 1;
+                    num_invalid_characters.1.get_or_insert(offset + i);
+                    removed_character_list.push((i - removed_length, c.len_utf8()));
+                    removed_length += c.len_utf8();
+                }
+                is_valid
+            })
+            .map(|(_, c)| c)
+            .collect();
+        let to_original_offset = |i: usize| -> usize {
+            offset
+                + i
+                + removed_character_list
+                    .iter()
+                    .take_while(|(j, _)| *j <= i)
+                    .map(|(_, len)| len)
+                    .sum::<usize>()
+        };
+        let mut last: usize = 0;
+        for c in entity_regex.captures_iter(&segment) {
+            let m = c.get(0).unwrap();
+            ret.push_str(&segment[last..m.start()]);
+            last = m.end();
+            match (c.get(1), c.get(2)) {
+                (Some(_), None) => ret.push_str(m.as_str()),
+                (Some(_), Some(name)) => match name.as_str() {
+                    "amp" | "lt" | "gt" | "quot" | "apos" => ret.push_str(m.as_str()),
+                    name => match html::find_named_entity(name) {
+                        Some(decoded) => {
+                            num_html_entities.0 += 1;
+                            num_html_entities
+                                .1
+                                .get_or_insert(to_original_offset(m.start()));
+                            ret.push_str(&format!("&#{};", decoded as u32));
+                        }
+                        None => {
+                            num_bare_ampersands.0 += 1;
+                            num_bare_ampersands
+                                .1
+                                .get_or_insert(to_original_offset(m.start()));
+                            ret.push_str("&amp;");
+                            ret.push_str(&m.as_str()[1..]);
+                        }
+                    },
+                },
+                _ => {
+                    num_bare_ampersands.0 += 1;
+                    num_bare_ampersands
+                        .1
+                        .get_or_insert(to_original_offset(m.start()));
+                    ret.push_str("&amp;");
+                }
+            }
+        }
+        ret.push_str(&segment[last..]);
+    };
+
+    let mut last: usize = 0;
+    for m in Regex::new(r"(?s)<!\[CDATA\[.*?\]\]>|<!--.*?-->")
+        .unwrap()
+        .find_iter(xml)
+    {
+        repair_segment(&xml[last..m.start()], last, &mut ret);
+        ret.push_str(m.as_str());
+        last = m.end();
+    }
+    repair_segment(&xml[last..], last, &mut ret);
+
+    let mut warning_list: Vec<String> = Vec::new();
+    for (description, (count, first_offset)) in [
+        ("Removed invalid character(s)", num_invalid_characters),
+        ("Replaced HTML entity reference(s)", num_html_entities),
+        ("Escaped bare ampersand(s)", num_bare_ampersands),
+    ] {
+        if let Some(first_offset) = first_offset {
+            let (line, column) = to_line_and_column(xml, first_offset);
+            warning_list.push(format!(
+                "{}: {} (first at line {}, column {})",
+                description, count, line, column
+            ));
+        }
+    }
+
+    (ret, warning_list)
+}
+
+//Both are 1-origin.
+fn to_line_and_column(s: &str, offset: usize) -> (usize, usize) {
+    let before: &str = &s[..offset];
+    let line: usize = before.matches('\n').count() + 1;
+    let column: usize = before.chars().rev().take_while(|c| *c != '\n').count() + 1;
+    (line, column)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_repair_valid_xml() {
+        let xml = "<a>&amp;&lt;&gt;&quot;&apos;&#38;&#x26;</a>";
+        assert_eq!((xml.to_string(), vec![]), repair(xml));
+    }
+
+    #[test]
+    fn test_repair() {
+        assert_eq!(
+            (
+                "<a>&#160;</a>".to_string(),
+                vec![
+                    "Removed invalid character(s): 1 (first at line 1, column 4)".to_string(),
+                    "Replaced HTML entity reference(s): 1 (first at line 1, column 5)".to_string(),
+                ]
+            ),
+            repair("<a>\u{1}&nbsp;</a>")
+        );
+        assert_eq!(
+            (
+                "<a>\n  x &amp; y &amp;foo;\n</a>".to_string(),
+                vec!["Escaped bare ampersand(s): 2 (first at line 2, column 5)".to_string()]
+            ),
+            repair("<a>\n  x & y &foo;\n</a>")
+        );
+    }
+
+    #[test]
+    fn test_repair_offsets_after_removed_characters() {
+        //Columns are counted in characters of the original text.
+        assert_eq!(
+            (
+                "<a>あ&#169;&amp;</a>".to_string(),
+                vec![
+                    "Removed invalid character(s): 2 (first at line 1, column 5)".to_string(),
+                    "Replaced HTML entity reference(s): 1 (first at line 1, column 7)".to_string(),
+                    "Escaped bare ampersand(s): 1 (first at line 1, column 13)".to_string(),
+                ]
+            ),
+            repair("<a>あ\u{1}\u{2}&copy;&</a>")
+        );
+    }
+
+    #[test]
+    fn test_repair_cdata_and_comments() {
+        assert_eq!(
+            (
+                "<a><![CDATA[&nbsp; \u{1}]]><!-- & -->&amp;</a>".to_string(),
+                vec!["Escaped bare ampersand(s): 1 (first at line 1, column 34)".to_string()]
+            ),
+            repair("<a><![CDATA[&nbsp; \u{1}]]><!-- & -->&</a>")
+        );
+    }
+}